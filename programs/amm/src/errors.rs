@@ -1,5 +1,4 @@
 use anchor_lang::error_code;
-use constant_product_curve::CurveError;
 
 #[error_code]
 pub enum AmmError {
@@ -39,18 +38,82 @@ pub enum AmmError {
     InsufficientBalance,
     #[msg("Zero balance.")]
     ZeroBalance,
+    #[msg("Invalid oracle price.")]
+    InvalidPrice,
+    #[msg("Oracle price is stale.")]
+    StalePrice,
+    #[msg("Oracle price confidence is too low.")]
+    PriceConfidenceTooLow,
+    #[msg("Invalid or missing price feed.")]
+    InvalidOracle,
+    #[msg("Invalid number of pool assets.")]
+    InvalidAssetCount,
+    #[msg("Unexpected remaining accounts.")]
+    InvalidAccountList,
+    #[msg("The sale has not started.")]
+    SaleNotStarted,
+    #[msg("The launch has not graduated yet.")]
+    LaunchNotGraduated,
+    #[msg("Flash loan is not repaid in the same transaction.")]
+    FlashLoanNotRepaid,
+    #[msg("No flash loan to repay.")]
+    NoFlashLoan,
+    #[msg("Flash loans can't be taken through CPI.")]
+    FlashLoanCpi,
+    #[msg("Flash swap input was not paid.")]
+    FlashSwapNotPaid,
+    #[msg("Invalid callback program.")]
+    InvalidCallbackProgram,
+    #[msg("No orders could be filled.")]
+    NoOrdersFilled,
+    #[msg("TWAP slice is not due yet.")]
+    TwapSliceNotReady,
+    #[msg("This pool only trades through batch auctions.")]
+    BatchModeOnly,
+    #[msg("Batch mode is off.")]
+    BatchModeOff,
+    #[msg("The batch is closed to new intents.")]
+    BatchClosed,
+    #[msg("The batch is full.")]
+    BatchFull,
+    #[msg("The batch can't be settled or claimed yet.")]
+    BatchNotReady,
+    #[msg("Already claimed.")]
+    AlreadyClaimed,
+    #[msg("Revealed swap doesn't match the commit.")]
+    InvalidReveal,
+    #[msg("Too early to reveal the commit.")]
+    RevealTooEarly,
+    #[msg("The commit has expired.")]
+    CommitExpired,
+    #[msg("The commit hasn't expired yet.")]
+    CommitNotExpired,
+    #[msg("Missing or invalid ed25519 signature.")]
+    InvalidSignature,
+    #[msg("The intent has expired.")]
+    IntentExpired,
+    #[msg("Invalid intent nonce.")]
+    InvalidNonce,
+    #[msg("Not an allowed market maker.")]
+    NotMarketMaker,
+    #[msg("The quote has expired.")]
+    QuoteExpired,
+    #[msg("The liquidity is still locked.")]
+    LiquidityStillLocked,
+    #[msg("The depositor's position account is required.")]
+    PositionRequired,
+    #[msg("The deposit would exceed the pool's deposit cap.")]
+    DepositCapExceeded,
+    #[msg("The deposit would exceed the per-wallet LP cap.")]
+    WalletCapExceeded,
+    #[msg("The launch hit its raise target and must graduate before trading again.")]
+    GraduationPending,
+    #[msg("Only one intent per user per batch.")]
+    DuplicateIntent,
+    #[msg("The intent is below the pool's minimum size.")]
+    IntentTooSmall,
+    #[msg("The quote has been filled in full.")]
+    QuoteFilled,
+    #[msg("The pool's config is already up to date.")]
+    ConfigUpToDate,
 }
-
-impl From<CurveError> for AmmError {
-    fn from(error: CurveError) -> AmmError {
-        match error {
-            CurveError::InvalidPrecision => AmmError::InvalidPrecision,
-            CurveError::Overflow => AmmError::Overflow,
-            CurveError::Underflow => AmmError::Underflow,
-            CurveError::InvalidFeeAmount => AmmError::InvalidFee,
-            CurveError::InsufficientBalance => AmmError::InsufficientBalance,
-            CurveError::ZeroBalance => AmmError::ZeroBalance,
-            CurveError::SlippageLimitExceeded => AmmError::SlippageExceeded,
-        }
-    }
-}
\ No newline at end of file
@@ -0,0 +1,520 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{associated_token::AssociatedToken, token::{transfer, Mint, Token, TokenAccount, Transfer}};
+
+use crate::{errors::AmmError, math, state::{Batch, BatchIntent, Config, CurveType, PriceFeed}};
+
+// This struct defines all the accounts needed to submit a swap intent to the current batch
+// The first intent of a batch creates it and starts its window
+#[derive(Accounts)]
+pub struct SubmitIntent<'info> {
+    // The user submitting the intent (pays for the accounts)
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // The two tokens that can be traded in this pool
+    pub mint_x: Account<'info, Mint>,
+    pub mint_y: Account<'info, Mint>,
+
+    // User's token accounts - the input is taken from one of them
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint_x,
+        associated_token::authority = user,
+    )]
+    pub user_x: Account<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint_y,
+        associated_token::authority = user,
+    )]
+    pub user_y: Account<'info, TokenAccount>,
+
+    // Pool configuration - tracks which batch is collecting intents
+    #[account(
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    // The batch currently collecting intents
+    #[account(
+        init_if_needed,
+        payer = user,
+        seeds = [b"batch", config.key().as_ref(), config.batch_id.to_le_bytes().as_ref()],
+        bump,
+        space = Batch::INIT_SPACE,
+    )]
+    pub batch: Account<'info, Batch>,
+
+    // Batch escrows - where intents' inputs wait for settlement
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint_x,
+        associated_token::authority = batch,
+    )]
+    pub escrow_x: Account<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint_y,
+        associated_token::authority = batch,
+    )]
+    pub escrow_y: Account<'info, TokenAccount>,
+
+    // Required program accounts
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> SubmitIntent<'info> {
+    // Escrow `amount` of one token to be swapped when the batch settles
+    pub fn submit(&mut self, is_x: bool, amount: u64, min_amount_out: u64, bumps: SubmitIntentBumps) -> Result<()> {
+        require!(self.config.batch_slots != 0, AmmError::BatchModeOff);
+        require!(!self.config.locked, AmmError::PoolLocked);
+        require!(self.config.curve != CurveType::BondingCurve, AmmError::LaunchNotGraduated);
+        require!(amount > 0, AmmError::InvalidAmount);
+
+        // A minimum size makes filling a batch with dust intents expensive
+        let min_intent = match is_x {
+            true => self.config.min_intent_x,
+            false => self.config.min_intent_y,
+        };
+        require!(amount >= min_intent, AmmError::IntentTooSmall);
+
+        let slot = Clock::get()?.slot;
+
+        // Start the batch's window with its first intent
+        if self.batch.config == Pubkey::default() {
+            self.batch.set_inner(Batch {
+                config: self.config.key(),
+                id: self.config.batch_id,
+                end_slot: slot.checked_add(self.config.batch_slots).ok_or(AmmError::Overflow)?,
+                settled: false,
+                total_x: 0,
+                total_y: 0,
+                x_out: 0,
+                y_out: 0,
+                intents: Vec::new(),
+                batch_bump: bumps.batch,
+            });
+        }
+
+        require!(slot < self.batch.end_slot, AmmError::BatchClosed);
+
+        self.batch.add_intent(BatchIntent {
+            owner: self.user.key(),
+            is_x,
+            amount,
+            min_amount_out,
+            filled: false,
+            claimed: false,
+        })?;
+
+        self.deposit_tokens(is_x, amount)
+    }
+
+    // Helper function to transfer the input from user to the batch escrow
+    pub fn deposit_tokens(&self, is_x: bool, amount: u64) -> Result<()> {
+        let (from, to) = match is_x {
+            true => (self.user_x.to_account_info(), self.escrow_x.to_account_info()),
+            false => (self.user_y.to_account_info(), self.escrow_y.to_account_info()),
+        };
+
+        let cpi_program = self.token_program.to_account_info();
+
+        let accounts = Transfer {
+            from,
+            to,
+            authority: self.user.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(cpi_program, accounts);
+
+        transfer(cpi_ctx, amount)
+    }
+}
+
+// This struct defines all the accounts needed to settle a batch once its window has ended
+// Anyone can settle a batch
+#[derive(Accounts)]
+pub struct SettleBatch<'info> {
+    // Whoever settles the batch
+    pub settler: Signer<'info>,
+
+    // The two tokens that can be traded in this pool
+    pub mint_x: Account<'info, Mint>,
+    pub mint_y: Account<'info, Mint>,
+
+    // Pool configuration - moves on to the next batch once this one settles
+    #[account(
+        mut,
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    // The batch being settled
+    #[account(
+        mut,
+        has_one = config,
+        seeds = [b"batch", config.key().as_ref(), batch.id.to_le_bytes().as_ref()],
+        bump = batch.batch_bump,
+    )]
+    pub batch: Account<'info, Batch>,
+
+    // Batch escrows - hold the inputs and receive the pool's output
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = batch,
+    )]
+    pub escrow_x: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = batch,
+    )]
+    pub escrow_y: Account<'info, TokenAccount>,
+
+    // Pool vaults - where the AMM stores the tokens
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = config,
+    )]
+    pub vault_x: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = config,
+    )]
+    pub vault_y: Account<'info, TokenAccount>,
+
+    // Price feed - only needed for oracle-anchored pools
+    pub price_feed: Option<Account<'info, PriceFeed>>,
+
+    // Required program accounts
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> SettleBatch<'info> {
+    // Clear every intent in the batch at one uniform price, trading only the imbalance against the pool
+    pub fn settle(&mut self) -> Result<()> {
+        require!(!self.config.locked, AmmError::PoolLocked);
+        require!(!self.batch.settled, AmmError::BatchNotReady);
+        require!(self.batch.id == self.config.batch_id, AmmError::BatchNotReady);
+        require!(Clock::get()?.slot >= self.batch.end_slot, AmmError::BatchNotReady);
+
+        // Read the oracle price if the pool is anchored to one
+        let price = self.config.oracle_price(
+            self.price_feed.as_ref(),
+            self.mint_x.decimals,
+            self.mint_y.decimals,
+        )?;
+
+        // Clear the batch, dropping intents whose minimum isn't met at the clearing price
+        // and clearing again until every remaining intent is satisfied
+        let mut intents = self.batch.intents.clone();
+        intents.iter_mut().for_each(|intent| intent.filled = true);
+
+        let (clearing, total_x, total_y) = loop {
+            let (total_x, total_y) = intents.iter().filter(|intent| intent.filled).try_fold((0u64, 0u64), |(x, y), intent| {
+                match intent.is_x {
+                    true => x.checked_add(intent.amount).map(|x| (x, y)),
+                    false => y.checked_add(intent.amount).map(|y| (x, y)),
+                }
+            }).ok_or(AmmError::Overflow)?;
+
+            let clearing = math::batch::clear_batch(total_x, total_y, |is_x, amount| {
+                // Quote on a copy so curve state isn't touched until the real trade
+                let mut config = Config::clone(&self.config);
+                Ok(config.pool_swap(is_x, amount, price)?.withdraw)
+            })?;
+
+            let mut dropped = false;
+            for intent in intents.iter_mut().filter(|intent| intent.filled) {
+                let amount_out = match intent.is_x {
+                    true => math::mul_div_floor(intent.amount, clearing.y_out, total_x)?,
+                    false => math::mul_div_floor(intent.amount, clearing.x_out, total_y)?,
+                };
+                if amount_out < intent.min_amount_out {
+                    intent.filled = false;
+                    dropped = true;
+                }
+            }
+
+            if !dropped {
+                break (clearing, total_x, total_y);
+            }
+        };
+
+        // Trade the imbalance against the pool, exactly like a regular swap
+        if clearing.amount_in != 0 {
+            let res = self.config.pool_swap(clearing.is_x, clearing.amount_in, price)?;
+            require!(res.withdraw == clearing.amount_out, AmmError::CurveError);
+            require!(res.withdraw != 0, AmmError::InvalidAmount);
+
+            let (escrow_in, vault_in, vault_out, escrow_out) = match clearing.is_x {
+                true => (&self.escrow_x, &self.vault_x, &self.vault_y, &self.escrow_y),
+                false => (&self.escrow_y, &self.vault_y, &self.vault_x, &self.escrow_x),
+            };
+            self.deposit_tokens(escrow_in.to_account_info(), vault_in.to_account_info(), res.deposit)?;
+            self.withdraw_tokens(vault_out.to_account_info(), escrow_out.to_account_info(), res.withdraw)?;
+        }
+
+        self.batch.intents = intents;
+        self.batch.settled = true;
+        self.batch.total_x = total_x;
+        self.batch.total_y = total_y;
+        self.batch.x_out = clearing.x_out;
+        self.batch.y_out = clearing.y_out;
+
+        // New intents go to the next batch
+        self.config.batch_id = self.config.batch_id.checked_add(1).ok_or(AmmError::Overflow)?;
+
+        Ok(())
+    }
+
+    // Helper function to transfer the imbalance from batch escrow to pool vault
+    pub fn deposit_tokens(&self, from: AccountInfo<'info>, to: AccountInfo<'info>, amount: u64) -> Result<()> {
+        let cpi_program = self.token_program.to_account_info();
+
+        let accounts = Transfer {
+            from,
+            to,
+            authority: self.batch.to_account_info(),
+        };
+
+        // Create the authority seeds for the batch account
+        let id = self.batch.id.to_le_bytes();
+        let seeds = &[
+            &b"batch"[..],
+            self.batch.config.as_ref(),
+            &id,
+            &[self.batch.batch_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, accounts, signer_seeds);
+
+        transfer(cpi_ctx, amount)
+    }
+
+    // Helper function to transfer the pool's output from pool vault to batch escrow
+    pub fn withdraw_tokens(&self, from: AccountInfo<'info>, to: AccountInfo<'info>, amount: u64) -> Result<()> {
+        let cpi_program = self.token_program.to_account_info();
+
+        let accounts = Transfer {
+            from,
+            to,
+            authority: self.config.to_account_info(),
+        };
+
+        // Create the authority seeds for the config account
+        let seeds = &[
+            &b"config"[..],
+            &self.config.seed.to_le_bytes(),
+            &[self.config.config_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, accounts, signer_seeds);
+
+        transfer(cpi_ctx, amount)
+    }
+}
+
+// This struct defines all the accounts needed to claim an intent's output (or refund) from a settled batch
+#[derive(Accounts)]
+pub struct ClaimBatch<'info> {
+    // The user who submitted the intent
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    // The two tokens that can be traded in this pool
+    pub mint_x: Account<'info, Mint>,
+    pub mint_y: Account<'info, Mint>,
+
+    // Owner's token accounts - the output (or refund) is sent to one of them
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = mint_x,
+        associated_token::authority = owner,
+    )]
+    pub owner_x: Account<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = mint_y,
+        associated_token::authority = owner,
+    )]
+    pub owner_y: Account<'info, TokenAccount>,
+
+    // Pool configuration - checks the mints and takes the batch's leftover dust into its reserves
+    #[account(
+        mut,
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    // The settled batch
+    #[account(
+        mut,
+        has_one = config,
+        seeds = [b"batch", config.key().as_ref(), batch.id.to_le_bytes().as_ref()],
+        bump = batch.batch_bump,
+    )]
+    pub batch: Account<'info, Batch>,
+
+    // Batch escrows - hold everything owed to the batch's intents
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = batch,
+    )]
+    pub escrow_x: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = batch,
+    )]
+    pub escrow_y: Account<'info, TokenAccount>,
+
+    // Pool vaults - receive the rounding dust left in the escrows after the last claim
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = config,
+    )]
+    pub vault_x: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = config,
+    )]
+    pub vault_y: Account<'info, TokenAccount>,
+
+    // Required program accounts
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ClaimBatch<'info> {
+    // Pay out the intent at `index`: its share of the clearing if it filled, or its input back if not
+    pub fn claim(&mut self, index: u8) -> Result<()> {
+        require!(self.batch.settled, AmmError::BatchNotReady);
+
+        let batch = &self.batch;
+        let intent = batch.intents.get(index as usize).ok_or(AmmError::InvalidAmount)?;
+        require_keys_eq!(intent.owner, self.owner.key(), AmmError::InvalidAuthority);
+        require!(!intent.claimed, AmmError::AlreadyClaimed);
+
+        // Outputs are shared in proportion to input, rounded down so the escrow always covers them
+        let (to_x, amount) = match (intent.filled, intent.is_x) {
+            (true, true) => (false, math::mul_div_floor(intent.amount, batch.y_out, batch.total_x)?),
+            (true, false) => (true, math::mul_div_floor(intent.amount, batch.x_out, batch.total_y)?),
+            (false, is_x) => (is_x, intent.amount),
+        };
+
+        self.batch.intents[index as usize].claimed = true;
+
+        if amount != 0 {
+            let (from, to) = match to_x {
+                true => (self.escrow_x.to_account_info(), self.owner_x.to_account_info()),
+                false => (self.escrow_y.to_account_info(), self.owner_y.to_account_info()),
+            };
+            self.withdraw_tokens(from, to, amount)?;
+        }
+
+        // Once everyone is paid, the escrows only hold rounding dust, which goes to the pool's LPs
+        if self.batch.all_claimed() {
+            self.sweep_dust()?;
+        }
+
+        Ok(())
+    }
+
+    // Move whatever is left in the escrows into the pool's vaults and reserves
+    pub fn sweep_dust(&mut self) -> Result<()> {
+        self.escrow_x.reload()?;
+        self.escrow_y.reload()?;
+        let (dust_x, dust_y) = (self.escrow_x.amount, self.escrow_y.amount);
+
+        self.config.record_deposit(dust_x, dust_y)?;
+        if dust_x != 0 {
+            self.withdraw_tokens(self.escrow_x.to_account_info(), self.vault_x.to_account_info(), dust_x)?;
+        }
+        if dust_y != 0 {
+            self.withdraw_tokens(self.escrow_y.to_account_info(), self.vault_y.to_account_info(), dust_y)?;
+        }
+
+        Ok(())
+    }
+
+    // Helper function to transfer tokens from batch escrow to owner
+    pub fn withdraw_tokens(&self, from: AccountInfo<'info>, to: AccountInfo<'info>, amount: u64) -> Result<()> {
+        let cpi_program = self.token_program.to_account_info();
+
+        let accounts = Transfer {
+            from,
+            to,
+            authority: self.batch.to_account_info(),
+        };
+
+        // Create the authority seeds for the batch account
+        let id = self.batch.id.to_le_bytes();
+        let seeds = &[
+            &b"batch"[..],
+            self.batch.config.as_ref(),
+            &id,
+            &[self.batch.batch_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, accounts, signer_seeds);
+
+        transfer(cpi_ctx, amount)
+    }
+}
+
+// This struct defines the accounts needed for the pool authority to turn batch mode on or off
+#[derive(Accounts)]
+pub struct SetBatchMode<'info> {
+    // The pool authority
+    pub authority: Signer<'info>,
+
+    // Pool configuration - stores the batch length
+    #[account(
+        mut,
+        constraint = config.authority == Some(authority.key()) @ AmmError::InvalidAuthority,
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+impl<'info> SetBatchMode<'info> {
+    // Set the batch length in slots, or 0 to go back to trading directly against the pool,
+    // and the smallest intent of each token a batch accepts
+    // A batch already collecting intents can still be settled after batch mode is turned off
+    pub fn set_batch_mode(&mut self, batch_slots: u64, min_intent_x: u64, min_intent_y: u64) -> Result<()> {
+        self.config.batch_slots = batch_slots;
+        self.config.min_intent_x = min_intent_x;
+        self.config.min_intent_y = min_intent_y;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,435 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{associated_token::AssociatedToken, token::{close_account, transfer, CloseAccount, Mint, Token, TokenAccount, Transfer}};
+
+use crate::{errors::AmmError, state::{Config, CurveType, PriceFeed, SwapCommit}};
+
+// This struct defines all the accounts needed to commit to a swap
+// The input is escrowed in accounts owned by the commit; depositing on both sides hides the direction
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+pub struct CommitSwap<'info> {
+    // The user committing to the swap (pays for the accounts)
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    // The two tokens that can be traded in this pool
+    pub mint_x: Account<'info, Mint>,
+    pub mint_y: Account<'info, Mint>,
+
+    // Owner's token accounts - the escrow is funded from them
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = mint_x,
+        associated_token::authority = owner,
+    )]
+    pub owner_x: Account<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = mint_y,
+        associated_token::authority = owner,
+    )]
+    pub owner_y: Account<'info, TokenAccount>,
+
+    // Pool configuration - contains all the pool settings
+    #[account(
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    // The new commit account
+    #[account(
+        init,
+        payer = owner,
+        seeds = [b"commit", config.key().as_ref(), owner.key().as_ref(), seed.to_le_bytes().as_ref()],
+        bump,
+        space = SwapCommit::INIT_SPACE,
+    )]
+    pub commit: Account<'info, SwapCommit>,
+
+    // Escrows holding the committed tokens
+    #[account(
+        init,
+        payer = owner,
+        associated_token::mint = mint_x,
+        associated_token::authority = commit,
+    )]
+    pub escrow_x: Account<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = owner,
+        associated_token::mint = mint_y,
+        associated_token::authority = commit,
+    )]
+    pub escrow_y: Account<'info, TokenAccount>,
+
+    // Required program accounts
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CommitSwap<'info> {
+    // Store the swap's hash and escrow `deposit_x` and `deposit_y`
+    pub fn commit(&mut self, seed: u64, hash: [u8; 32], deposit_x: u64, deposit_y: u64, bumps: CommitSwapBumps) -> Result<()> {
+        require!(deposit_x > 0 || deposit_y > 0, AmmError::InvalidAmount);
+
+        self.commit.set_inner(SwapCommit {
+            owner: self.owner.key(),
+            config: self.config.key(),
+            seed,
+            hash,
+            commit_slot: Clock::get()?.slot,
+            commit_bump: bumps.commit,
+        });
+
+        if deposit_x > 0 {
+            self.deposit_tokens(true, deposit_x)?;
+        }
+        if deposit_y > 0 {
+            self.deposit_tokens(false, deposit_y)?;
+        }
+
+        Ok(())
+    }
+
+    // Helper function to transfer tokens from owner to escrow
+    pub fn deposit_tokens(&self, is_x: bool, amount: u64) -> Result<()> {
+        let (from, to) = match is_x {
+            true => (self.owner_x.to_account_info(), self.escrow_x.to_account_info()),
+            false => (self.owner_y.to_account_info(), self.escrow_y.to_account_info()),
+        };
+
+        let cpi_program = self.token_program.to_account_info();
+
+        let accounts = Transfer {
+            from,
+            to,
+            authority: self.owner.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(cpi_program, accounts);
+
+        transfer(cpi_ctx, amount)
+    }
+}
+
+// This struct defines all the accounts needed to reveal a committed swap and execute it
+#[derive(Accounts)]
+pub struct RevealSwap<'info> {
+    // The user who committed the swap (receives the rent back)
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    // The two tokens that can be traded in this pool
+    pub mint_x: Account<'info, Mint>,
+    pub mint_y: Account<'info, Mint>,
+
+    // Owner's token accounts - the output and any unused escrow are sent here
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = owner,
+    )]
+    pub owner_x: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = owner,
+    )]
+    pub owner_y: Account<'info, TokenAccount>,
+
+    // Pool configuration - contains all the pool settings
+    #[account(
+        mut,
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    // The commit being revealed
+    #[account(
+        mut,
+        close = owner,
+        has_one = owner,
+        has_one = config,
+        seeds = [b"commit", config.key().as_ref(), owner.key().as_ref(), commit.seed.to_le_bytes().as_ref()],
+        bump = commit.commit_bump,
+    )]
+    pub commit: Account<'info, SwapCommit>,
+
+    // Escrows holding the committed tokens
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = commit,
+    )]
+    pub escrow_x: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = commit,
+    )]
+    pub escrow_y: Account<'info, TokenAccount>,
+
+    // Pool vaults - where the AMM stores the tokens
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = config,
+    )]
+    pub vault_x: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = config,
+    )]
+    pub vault_y: Account<'info, TokenAccount>,
+
+    // Price feed - only needed for oracle-anchored pools
+    pub price_feed: Option<Account<'info, PriceFeed>>,
+
+    // Required program accounts
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> RevealSwap<'info> {
+    // Check the revealed swap against the commit and execute it through the pool's curve
+    pub fn reveal(&mut self, is_x: bool, amount: u64, min: u64, salt: [u8; 32]) -> Result<()> {
+        // Check that the pool is not locked
+        require!(!self.config.locked, AmmError::PoolLocked);
+        self.config.check_direct_trading()?;
+        require!(amount > 0, AmmError::InvalidAmount);
+
+        // Launches only trade through `swap`, which stops them at the raise target, so they can't be traded through a commit
+        require!(self.config.curve != CurveType::BondingCurve, AmmError::LaunchNotGraduated);
+
+        // The reveal must match the commit and fall between the delay and the expiry
+        require!(SwapCommit::commitment(is_x, amount, min, &salt) == self.commit.hash, AmmError::InvalidReveal);
+        self.commit.check_reveal(Clock::get()?.slot)?;
+
+        // Read the oracle price if the pool is anchored to one
+        let price = self.config.oracle_price(
+            self.price_feed.as_ref(),
+            self.mint_x.decimals,
+            self.mint_y.decimals,
+        )?;
+
+        // Calculate the swap using the pool's pricing curve, exactly like a regular swap
+        let res = self.config.pool_swap(is_x, amount, price)?;
+
+        // Check that the output meets the committed minimum
+        require!(res.withdraw >= min, AmmError::SlippageExceeded);
+
+        // Verify the swap amounts are valid
+        require!(res.deposit != 0, AmmError::InvalidAmount);
+        require!(res.withdraw != 0, AmmError::InvalidAmount);
+
+        // Pay the input from escrow and the output to the owner
+        let (escrow_in, vault_in, vault_out, owner_out) = match is_x {
+            true => (&self.escrow_x, &self.vault_x, &self.vault_y, &self.owner_y),
+            false => (&self.escrow_y, &self.vault_y, &self.vault_x, &self.owner_x),
+        };
+        require!(escrow_in.amount >= res.deposit, AmmError::InsufficientBalance);
+        self.escrow_transfer(escrow_in.to_account_info(), vault_in.to_account_info(), res.deposit)?;
+        self.withdraw_tokens(vault_out.to_account_info(), owner_out.to_account_info(), res.withdraw)?;
+
+        // Return whatever the swap didn't use and close the escrows
+        self.escrow_x.reload()?;
+        self.escrow_y.reload()?;
+        self.close_escrows()
+    }
+
+    // Refund both escrows to the owner and close them
+    pub fn close_escrows(&self) -> Result<()> {
+        for (escrow, owner_account) in [(&self.escrow_x, &self.owner_x), (&self.escrow_y, &self.owner_y)] {
+            if escrow.amount != 0 {
+                self.escrow_transfer(escrow.to_account_info(), owner_account.to_account_info(), escrow.amount)?;
+            }
+            self.close_escrow(escrow.to_account_info())?;
+        }
+
+        Ok(())
+    }
+
+    // Helper function to transfer tokens out of an escrow
+    pub fn escrow_transfer(&self, from: AccountInfo<'info>, to: AccountInfo<'info>, amount: u64) -> Result<()> {
+        let commit = &self.commit;
+
+        let accounts = Transfer {
+            from,
+            to,
+            authority: commit.to_account_info(),
+        };
+
+        // Create the authority seeds for the commit account
+        let seed = commit.seed.to_le_bytes();
+        let seeds = &[
+            &b"commit"[..],
+            commit.config.as_ref(),
+            commit.owner.as_ref(),
+            &seed,
+            &[commit.commit_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), accounts, signer_seeds);
+
+        transfer(cpi_ctx, amount)
+    }
+
+    // Helper function to close an emptied escrow, returning its rent to the owner
+    pub fn close_escrow(&self, escrow: AccountInfo<'info>) -> Result<()> {
+        let commit = &self.commit;
+
+        let accounts = CloseAccount {
+            account: escrow,
+            destination: self.owner.to_account_info(),
+            authority: commit.to_account_info(),
+        };
+
+        // Create the authority seeds for the commit account
+        let seed = commit.seed.to_le_bytes();
+        let seeds = &[
+            &b"commit"[..],
+            commit.config.as_ref(),
+            commit.owner.as_ref(),
+            &seed,
+            &[commit.commit_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), accounts, signer_seeds);
+
+        close_account(cpi_ctx)
+    }
+
+    // Helper function to transfer tokens from pool vault to owner
+    pub fn withdraw_tokens(&self, from: AccountInfo<'info>, to: AccountInfo<'info>, amount: u64) -> Result<()> {
+        let cpi_program = self.token_program.to_account_info();
+
+        let accounts = Transfer {
+            from,
+            to,
+            authority: self.config.to_account_info(),
+        };
+
+        // Create the authority seeds for the config account
+        let seeds = &[
+            &b"config"[..],
+            &self.config.seed.to_le_bytes(),
+            &[self.config.config_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, accounts, signer_seeds);
+
+        transfer(cpi_ctx, amount)
+    }
+}
+
+// This struct defines all the accounts needed to reclaim an expired commit
+#[derive(Accounts)]
+pub struct ReclaimCommit<'info> {
+    // The user who committed the swap (receives the rent back)
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    // The two tokens that can be traded in the pool
+    pub mint_x: Account<'info, Mint>,
+    pub mint_y: Account<'info, Mint>,
+
+    // Owner's token accounts - the escrow is refunded here
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = owner,
+    )]
+    pub owner_x: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = owner,
+    )]
+    pub owner_y: Account<'info, TokenAccount>,
+
+    // The expired commit
+    #[account(
+        mut,
+        close = owner,
+        has_one = owner,
+        seeds = [b"commit", commit.config.as_ref(), owner.key().as_ref(), commit.seed.to_le_bytes().as_ref()],
+        bump = commit.commit_bump,
+    )]
+    pub commit: Account<'info, SwapCommit>,
+
+    // Escrows holding the committed tokens
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = commit,
+    )]
+    pub escrow_x: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = commit,
+    )]
+    pub escrow_y: Account<'info, TokenAccount>,
+
+    // Required program accounts
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> ReclaimCommit<'info> {
+    // Refund an unrevealed commit once it has expired
+    pub fn reclaim(&mut self) -> Result<()> {
+        require!(self.commit.expired(Clock::get()?.slot), AmmError::CommitNotExpired);
+
+        self.close_escrows()
+    }
+
+    // Refund both escrows to the owner and close them
+    pub fn close_escrows(&self) -> Result<()> {
+        let commit = &self.commit;
+
+        // Create the authority seeds for the commit account
+        let seed = commit.seed.to_le_bytes();
+        let seeds = &[
+            &b"commit"[..],
+            commit.config.as_ref(),
+            commit.owner.as_ref(),
+            &seed,
+            &[commit.commit_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        for (escrow, owner_account) in [(&self.escrow_x, &self.owner_x), (&self.escrow_y, &self.owner_y)] {
+            if escrow.amount != 0 {
+                let accounts = Transfer {
+                    from: escrow.to_account_info(),
+                    to: owner_account.to_account_info(),
+                    authority: commit.to_account_info(),
+                };
+                let cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), accounts, signer_seeds);
+                transfer(cpi_ctx, escrow.amount)?;
+            }
+
+            let accounts = CloseAccount {
+                account: escrow.to_account_info(),
+                destination: self.owner.to_account_info(),
+                authority: commit.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), accounts, signer_seeds);
+            close_account(cpi_ctx)?;
+        }
+
+        Ok(())
+    }
+}
@@ -1,8 +1,6 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{associated_token::AssociatedToken, token::{Transfer, transfer, Mint, Token, TokenAccount, MintTo, mint_to}};
-use constant_product_curve::ConstantProduct;
-
-use crate::{errors::AmmError, state::Config};
+use crate::{errors::AmmError, math, state::{Config, LpPosition}};
 
 // This struct defines all the accounts needed to deposit liquidity into the AMM
 // Users can add both tokens to earn trading fees
@@ -18,6 +16,7 @@ pub struct Deposit<'info> {
     
     // Pool configuration - contains all the pool settings
     #[account(
+        mut,
         has_one = mint_x,
         has_one = mint_y,
         seeds = [b"config", config.seed.to_le_bytes().as_ref()],
@@ -69,6 +68,15 @@ pub struct Deposit<'info> {
         associated_token::authority = user,
     )]
     pub user_lp: Account<'info, TokenAccount>,
+
+    // Depositor's position - only needed to track fee earnings for this depositor
+    #[account(
+        mut,
+        has_one = config,
+        seeds = [b"position", config.key().as_ref(), user.key().as_ref()],
+        bump = position.position_bump,
+    )]
+    pub position: Option<Account<'info, LpPosition>>,
     
     // Required program accounts
     pub token_program: Program<'info, Token>,
@@ -85,22 +93,25 @@ impl<'info> Deposit<'info> {
         max_y: u64, // Maximum amount of token Y that the user is willing to deposit
     ) -> Result<()> {
         // Check that the pool is not locked
-        require!(self.config.locked == false, AmmError::PoolLocked);
+        require!(!self.config.locked, AmmError::PoolLocked);
         require!(amount != 0, AmmError::InvalidAmount);
 
+        // Liquidity may be restricted to the pool authority (e.g. during a sale)
+        self.config.check_liquidity_provider(self.user.key())?;
+        self.config.check_position_required(self.position.is_some())?;
+
         // Calculate how much of each token to deposit
-        let (x, y) = match self.mint_lp.supply == 0 && self.vault_x.amount == 0 && self.vault_y.amount == 0 {
+        let (x, y) = match self.mint_lp.supply == 0 && self.config.reserve_x == 0 && self.config.reserve_y == 0 {
             // If this is the first deposit, use the maximum amounts
             true => (max_x, max_y),
             // Otherwise, calculate the correct ratio based on current pool state
             false => {
-                let amounts = ConstantProduct::xy_deposit_amounts_from_l(
-                    self.vault_x.amount, 
-                    self.vault_y.amount, 
+                let amounts = math::deposit_amounts(
+                    self.config.reserve_x, 
+                    self.config.reserve_y, 
                     self.mint_lp.supply, 
                     amount, 
-                    6
-                ).unwrap();
+                )?;
                 (amounts.x, amounts.y)
             }
         };
@@ -108,14 +119,83 @@ impl<'info> Deposit<'info> {
         // Check that the calculated amounts don't exceed user's limits
         require!(x <= max_x && y <= max_y, AmmError::SlippageExceeded);
 
+        // Keep the PMM equilibrium in step with the pool's liquidity
+        self.config.deposit_targets(x, y, amount, self.mint_lp.supply)?;
+
         // Transfer tokens from user to pool vaults
+        self.config.record_deposit(x, y)?;
         self.deposit_tokens(true, x)?;  // Deposit token X
         self.deposit_tokens(false, y)?; // Deposit token Y
+
+        // Track the new LP for fee accounting
+        self.config.record_lp_minted(amount)?;
+        if let Some(position) = self.position.as_mut() {
+            position.settle(&self.config)?;
+            position.record_deposit(x, y, amount)?;
+        }
+
+        // Guarded pools cap their size and how much LP one wallet can take
+        self.config.check_deposit_caps(self.position.as_ref().map(|position| position.lp))?;
         
         // Give LP tokens to the user
         self.mint_lp_tokens(amount)
     }
 
+    // Deposit exactly `amount` of X (or Y), with the program working out the matching amount
+    // of the other token and the LP minted, both rounded against the depositor
+    pub fn deposit_exact(
+        &mut self,
+        is_x: bool, // Whether `amount` is token X
+        amount: u64, // Exact amount of that token to deposit
+        max_other: u64, // Maximum amount of the other token that the user is willing to deposit
+        min_lp: u64, // Minimum amount of LP tokens that the user wants to receive
+    ) -> Result<()> {
+        // Check that the pool is not locked
+        require!(!self.config.locked, AmmError::PoolLocked);
+        require!(amount != 0, AmmError::InvalidAmount);
+
+        // Liquidity may be restricted to the pool authority (e.g. during a sale)
+        self.config.check_liquidity_provider(self.user.key())?;
+        self.config.check_position_required(self.position.is_some())?;
+
+        // Calculate the other side and the LP from the current pool state
+        let (reserve_in, reserve_other) = match is_x {
+            true => (self.config.reserve_x, self.config.reserve_y),
+            false => (self.config.reserve_y, self.config.reserve_x),
+        };
+        let (lp, other) = math::exact_deposit_amounts(reserve_in, reserve_other, self.mint_lp.supply, amount)?;
+
+        // Check the amounts against the user's limits
+        require!(lp != 0, AmmError::InvalidAmount);
+        require!(lp >= min_lp && other <= max_other, AmmError::SlippageExceeded);
+
+        let (x, y) = match is_x {
+            true => (amount, other),
+            false => (other, amount),
+        };
+
+        // Keep the PMM equilibrium in step with the pool's liquidity
+        self.config.deposit_targets(x, y, lp, self.mint_lp.supply)?;
+
+        // Transfer tokens from user to pool vaults
+        self.config.record_deposit(x, y)?;
+        self.deposit_tokens(true, x)?;
+        self.deposit_tokens(false, y)?;
+
+        // Track the new LP for fee accounting
+        self.config.record_lp_minted(lp)?;
+        if let Some(position) = self.position.as_mut() {
+            position.settle(&self.config)?;
+            position.record_deposit(x, y, lp)?;
+        }
+
+        // Guarded pools cap their size and how much LP one wallet can take
+        self.config.check_deposit_caps(self.position.as_ref().map(|position| position.lp))?;
+
+        // Give LP tokens to the user
+        self.mint_lp_tokens(lp)
+    }
+
     // Helper function to transfer tokens from user to pool vault
     pub fn deposit_tokens(&self, is_x: bool, amount: u64) -> Result<()> {
         let (from, to) = match is_x {
@@ -159,4 +239,34 @@ impl<'info> Deposit<'info> {
 
         mint_to(ctx, amount)
     }
-}
\ No newline at end of file
+}
+// This struct defines the accounts needed for the pool authority to cap deposits into the pool
+// Lets a new pool launch with bounded risk and raise the caps as it proves itself
+#[derive(Accounts)]
+pub struct SetDepositCaps<'info> {
+    // The pool authority
+    pub authority: Signer<'info>,
+
+    // Pool configuration - stores the caps
+    #[account(
+        mut,
+        constraint = config.authority == Some(authority.key()) @ AmmError::InvalidAuthority,
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+impl<'info> SetDepositCaps<'info> {
+    // Set the most X and Y the reserves, the LP supply and one wallet's LP can reach through deposits (0 = no cap)
+    // Caps only stop new deposits, so lowering one below the current level never forces anyone out
+    // The wallet cap is tracked through the depositor's position, which deposits must then pass
+    pub fn set_deposit_caps(&mut self, max_reserve_x: u64, max_reserve_y: u64, max_lp_supply: u64, max_wallet_lp: u64) -> Result<()> {
+        self.config.max_reserve_x = max_reserve_x;
+        self.config.max_reserve_y = max_reserve_y;
+        self.config.max_lp_supply = max_lp_supply;
+        self.config.max_wallet_lp = max_wallet_lp;
+
+        Ok(())
+    }
+}
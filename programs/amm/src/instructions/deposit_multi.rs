@@ -0,0 +1,115 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{associated_token::AssociatedToken, token::{mint_to, transfer, Mint, MintTo, Token, TokenAccount, Transfer}};
+
+use crate::{errors::AmmError, state::MultiPool};
+
+// This struct defines all the accounts needed to deposit into a multi-asset pool
+// Vaults and user token accounts are passed as remaining accounts in mint order:
+// [vault_0, user_0, vault_1, user_1, ...]
+#[derive(Accounts)]
+pub struct DepositMulti<'info> {
+    // The user who wants to add liquidity (pays for the transaction)
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // Pool account - contains the list of mints, reserves and pool settings
+    #[account(
+        mut,
+        seeds = [b"multi", pool.seed.to_le_bytes().as_ref()],
+        bump = pool.pool_bump,
+    )]
+    pub pool: Account<'info, MultiPool>,
+
+    // LP token mint - represents the user's share of the pool
+    #[account(
+        mut,
+        seeds = [b"lp", pool.key().as_ref()],
+        bump = pool.lp_bump,
+    )]
+    pub mint_lp: Account<'info, Mint>,
+
+    // User's LP token account - where they receive their LP tokens
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint_lp,
+        associated_token::authority = user,
+    )]
+    pub user_lp: Account<'info, TokenAccount>,
+
+    // Required program accounts
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> DepositMulti<'info> {
+    // Main deposit function - adds liquidity in proportion to every vault
+    pub fn deposit(
+        &mut self,
+        amount: u64,           // Amount of LP tokens that the user wants to "claim"
+        max_amounts: Vec<u64>, // Maximum amount of each token the user is willing to deposit
+        remaining_accounts: &'info [AccountInfo<'info>],
+    ) -> Result<()> {
+        // Check that the pool is not locked
+        require!(!self.pool.locked, AmmError::PoolLocked);
+        require!(amount != 0, AmmError::InvalidAmount);
+
+        let count = self.pool.mints.len();
+        require!(remaining_accounts.len() == count * 2, AmmError::InvalidAccountList);
+
+        // The first deposit sets the prices, later ones match the pool's reserves
+        let amounts = self.pool.deposit_amounts(amount, self.mint_lp.supply, &max_amounts)?;
+
+        let pool_key = self.pool.key();
+        for (i, accounts) in remaining_accounts.chunks(2).enumerate() {
+            let vault = self.pool.vault(pool_key, i, &accounts[0])?;
+            let user_token = self.pool.token_account(i, &accounts[1])?;
+
+            self.pool.record_deposit(i, amounts[i])?;
+            self.deposit_tokens(user_token.to_account_info(), vault.to_account_info(), amounts[i])?;
+        }
+
+        // Give LP tokens to the user
+        self.mint_lp_tokens(amount)
+    }
+
+    // Helper function to transfer tokens from user to pool vault
+    pub fn deposit_tokens(&self, from: AccountInfo<'info>, to: AccountInfo<'info>, amount: u64) -> Result<()> {
+        let cpi_program = self.token_program.to_account_info();
+
+        let cpi_accounts = Transfer {
+            from,
+            to,
+            authority: self.user.to_account_info(),
+        };
+
+        let ctx = CpiContext::new(cpi_program, cpi_accounts);
+
+        transfer(ctx, amount)
+    }
+
+    // Helper function to mint LP tokens for the user
+    pub fn mint_lp_tokens(&self, amount: u64) -> Result<()> {
+        let cpi_program = self.token_program.to_account_info();
+
+        let cpi_accounts = MintTo {
+            mint: self.mint_lp.to_account_info(),
+            to: self.user_lp.to_account_info(),
+            authority: self.pool.to_account_info(),
+        };
+
+        // Create the authority seeds for the pool account
+        let seeds = &[
+            &b"multi"[..],
+            &self.pool.seed.to_le_bytes(),
+            &[self.pool.pool_bump],
+        ];
+
+        let signer_seeds = &[&seeds[..]];
+
+        let ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+        mint_to(ctx, amount)
+    }
+}
@@ -0,0 +1,251 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{associated_token::AssociatedToken, token::{Transfer, transfer, Mint, Token, TokenAccount, MintTo, mint_to}};
+use crate::{errors::AmmError, math, state::{Config, LpPosition, PriceFeed}};
+
+// This struct defines all the accounts needed to deposit liquidity with only one of the pool's tokens
+// Part of the deposit is swapped through the pool so the rest can be added as balanced liquidity
+#[derive(Accounts)]
+pub struct DepositSingle<'info> {
+    // The user who wants to add liquidity (pays for the transaction)
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // The two tokens that can be traded in this pool
+    pub mint_x: Account<'info, Mint>,
+    pub mint_y: Account<'info, Mint>,
+
+    // Pool configuration - contains all the pool settings
+    #[account(
+        mut,
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    // Price feed - only needed for oracle-anchored pools
+    pub price_feed: Option<Account<'info, PriceFeed>>,
+
+    // LP token mint - represents the user's share of the pool
+    #[account(
+        mut,
+        seeds = [b"lp", config.key().as_ref()],
+        bump = config.lp_bump,
+    )]
+    pub mint_lp: Account<'info, Mint>,
+
+    // Pool vaults - where the AMM stores the tokens
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = config,
+    )]
+    pub vault_x: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = config,
+    )]
+    pub vault_y: Account<'info, TokenAccount>,
+
+    // User's token accounts - the deposit comes from one, and any leftover of the other side is returned
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint_x,
+        associated_token::authority = user,
+    )]
+    pub user_x: Account<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint_y,
+        associated_token::authority = user,
+    )]
+    pub user_y: Account<'info, TokenAccount>,
+
+    // User's LP token account - where they receive their LP tokens
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint_lp,
+        associated_token::authority = user,
+    )]
+    pub user_lp: Account<'info, TokenAccount>,
+
+    // Depositor's position - only needed to track fee earnings for this depositor
+    #[account(
+        mut,
+        has_one = config,
+        seeds = [b"position", config.key().as_ref(), user.key().as_ref()],
+        bump = position.position_bump,
+    )]
+    pub position: Option<Account<'info, LpPosition>>,
+
+    // Required program accounts
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> DepositSingle<'info> {
+    // Deposit `amount` of X (or Y), swapping the right part of it through the pool's curve
+    // (paying the swap fee) and minting LP for the balanced result
+    pub fn deposit_single(&mut self, is_x: bool, amount: u64, min_lp_out: u64) -> Result<()> {
+        // Check that the pool is not locked
+        require!(!self.config.locked, AmmError::PoolLocked);
+        self.config.check_direct_trading()?;
+        require!(amount != 0, AmmError::InvalidAmount);
+
+        // Liquidity may be restricted to the pool authority (e.g. during a sale)
+        self.config.check_liquidity_provider(self.user.key())?;
+        self.config.check_position_required(self.position.is_some())?;
+
+        // Read the oracle price if the pool is anchored to one
+        let price = self.config.oracle_price(
+            self.price_feed.as_ref(),
+            self.mint_x.decimals,
+            self.mint_y.decimals,
+        )?;
+
+        // Size the swap by quoting the curve on a copy of the config
+        let (reserve_x, reserve_y) = (self.config.reserve_x, self.config.reserve_y);
+        let (reserve_in, reserve_out) = match is_x {
+            true => (reserve_x, reserve_y),
+            false => (reserve_y, reserve_x),
+        };
+        let supply = self.mint_lp.supply;
+        let zap = math::zap::zap_in(amount, reserve_in, reserve_out, supply, |amount_in| {
+            let mut config = Config::clone(&self.config);
+            Ok(config.curve_swap(is_x, amount_in, reserve_x, reserve_y, price)?.withdraw)
+        })?;
+
+        // Check that the LP meets the user's minimum
+        require!(zap.lp != 0, AmmError::InvalidAmount);
+        require!(zap.lp >= min_lp_out, AmmError::SlippageExceeded);
+
+        // Run the swap on the real curve so any curve state moves with it
+        if zap.swap_in != 0 {
+            let res = self.config.pool_swap(is_x, zap.swap_in, price)?;
+            require!(res.withdraw == zap.swap_out, AmmError::CurveError);
+        }
+
+        // Work out the balanced deposit against the post-swap reserves, rounded up like `deposit`
+        let reserve_in = reserve_in.checked_add(zap.swap_in).ok_or(AmmError::Overflow)?;
+        let reserve_out = reserve_out.checked_sub(zap.swap_out).ok_or(AmmError::Underflow)?;
+        let (x, y) = match is_x {
+            true => (reserve_in, reserve_out),
+            false => (reserve_out, reserve_in),
+        };
+        let amounts = math::deposit_amounts(x, y, supply, zap.lp)?;
+        let (deposit_in, deposit_out) = match is_x {
+            true => (amounts.x, amounts.y),
+            false => (amounts.y, amounts.x),
+        };
+
+        // Keep the PMM equilibrium in step with the pool's liquidity
+        self.config.deposit_targets(amounts.x, amounts.y, zap.lp, supply)?;
+        self.config.record_deposit(amounts.x, amounts.y)?;
+
+        // The user pays the swap input and the input side of the deposit, and gets back
+        // whatever swap output the deposit didn't need
+        let paid = zap.swap_in.checked_add(deposit_in).ok_or(AmmError::Overflow)?;
+        require!(paid <= amount, AmmError::InvalidAmount);
+        self.deposit_tokens(is_x, paid)?;
+
+        let leftover = zap.swap_out.checked_sub(deposit_out).ok_or(AmmError::Underflow)?;
+        if leftover != 0 {
+            self.withdraw_tokens(!is_x, leftover)?;
+        }
+
+        // Track the new LP for fee accounting, after the swap so its fee isn't credited to this LP
+        self.config.record_lp_minted(zap.lp)?;
+        if let Some(position) = self.position.as_mut() {
+            position.settle(&self.config)?;
+            // The user only ever pays in the deposited token
+            let (x, y) = match is_x {
+                true => (paid, 0),
+                false => (0, paid),
+            };
+            position.record_deposit(x, y, zap.lp)?;
+        }
+
+        // Guarded pools cap their size and how much LP one wallet can take
+        self.config.check_deposit_caps(self.position.as_ref().map(|position| position.lp))?;
+
+        // Give LP tokens to the user
+        self.mint_lp_tokens(zap.lp)
+    }
+
+    // Helper function to transfer tokens from user to pool vault
+    pub fn deposit_tokens(&self, is_x: bool, amount: u64) -> Result<()> {
+        let (from, to) = match is_x {
+            true => (self.user_x.to_account_info(), self.vault_x.to_account_info()),
+            false => (self.user_y.to_account_info(), self.vault_y.to_account_info()),
+        };
+
+        let cpi_program = self.token_program.to_account_info();
+
+        let cpi_accounts = Transfer {
+            from,
+            to,
+            authority: self.user.to_account_info(),
+        };
+
+        let ctx = CpiContext::new(cpi_program, cpi_accounts);
+
+        transfer(ctx, amount)
+    }
+
+    // Helper function to transfer tokens from pool vault to user
+    pub fn withdraw_tokens(&self, is_x: bool, amount: u64) -> Result<()> {
+        let (from, to) = match is_x {
+            true => (self.vault_x.to_account_info(), self.user_x.to_account_info()),
+            false => (self.vault_y.to_account_info(), self.user_y.to_account_info()),
+        };
+
+        let cpi_program = self.token_program.to_account_info();
+
+        let cpi_accounts = Transfer {
+            from,
+            to,
+            authority: self.config.to_account_info(),
+        };
+
+        // Create the authority seeds for the config account
+        let seeds = &[
+            &b"config"[..],
+            &self.config.seed.to_le_bytes(),
+            &[self.config.config_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        let ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+        transfer(ctx, amount)
+    }
+
+    // Helper function to mint LP tokens for the user
+    pub fn mint_lp_tokens(&self, amount: u64) -> Result<()> {
+        let cpi_program = self.token_program.to_account_info();
+
+        let cpi_accounts = MintTo {
+            mint: self.mint_lp.to_account_info(),
+            to: self.user_lp.to_account_info(),
+            authority: self.config.to_account_info(),
+        };
+
+        // Create the authority seeds for the config account
+        let seeds = &[
+            &b"config"[..],
+            &self.config.seed.to_le_bytes(),
+            &[self.config.config_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        let ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+        mint_to(ctx, amount)
+    }
+}
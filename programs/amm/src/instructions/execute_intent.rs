@@ -0,0 +1,199 @@
+use anchor_lang::{prelude::*, solana_program::sysvar::instructions};
+use anchor_spl::{associated_token::AssociatedToken, token::{transfer, Mint, Token, TokenAccount, Transfer}};
+
+use crate::{errors::AmmError, signature, state::{Config, CurveType, IntentNonce, PriceFeed}};
+
+// This struct defines all the accounts needed for a relayer to execute a user's signed swap intent
+// The user approves the pool's config as delegate on their input token account, signs the intent
+// off-chain, and the relayer submits it with an ed25519 program instruction right before this one
+#[derive(Accounts)]
+pub struct ExecuteIntent<'info> {
+    // The relayer submitting the intent (pays for the transaction)
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+
+    // The user who signed the intent - doesn't need to sign the transaction or hold SOL
+    pub user: SystemAccount<'info>,
+
+    // The two tokens that can be traded in this pool
+    pub mint_x: Account<'info, Mint>,
+    pub mint_y: Account<'info, Mint>,
+
+    // User's token accounts - the input is pulled from one through the delegation, and the output sent to the other
+    #[account(
+        init_if_needed,
+        payer = relayer,
+        associated_token::mint = mint_x,
+        associated_token::authority = user,
+    )]
+    pub user_x: Account<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = relayer,
+        associated_token::mint = mint_y,
+        associated_token::authority = user,
+    )]
+    pub user_y: Account<'info, TokenAccount>,
+
+    // Relayer's token accounts - the tip is paid in the input token
+    #[account(
+        init_if_needed,
+        payer = relayer,
+        associated_token::mint = mint_x,
+        associated_token::authority = relayer,
+    )]
+    pub relayer_x: Account<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = relayer,
+        associated_token::mint = mint_y,
+        associated_token::authority = relayer,
+    )]
+    pub relayer_y: Account<'info, TokenAccount>,
+
+    // Pool vaults - where the AMM stores the tokens
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = config,
+    )]
+    pub vault_x: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = config,
+    )]
+    pub vault_y: Account<'info, TokenAccount>,
+
+    // Pool configuration - also the delegate on the user's input account
+    #[account(
+        mut,
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    // Price feed - only needed for oracle-anchored pools
+    pub price_feed: Option<Account<'info, PriceFeed>>,
+
+    // The user's intent nonce, created with their first intent
+    #[account(
+        init_if_needed,
+        payer = relayer,
+        seeds = [b"nonce", user.key().as_ref()],
+        bump,
+        space = IntentNonce::INIT_SPACE,
+    )]
+    pub nonce: Account<'info, IntentNonce>,
+
+    /// CHECK: The instructions sysvar, used to find the ed25519 signature check
+    #[account(address = instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    // Required program accounts
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+// The swap a user signs off-chain; the borsh-serialized struct is the signed message
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct SwapIntent {
+    pub pool: Pubkey, // Config of the pool to trade against
+    pub user: Pubkey, // User signing the intent
+    pub is_x: bool, // Whether the input is token X
+    pub amount_in: u64, // Amount of the input token to swap
+    pub min_amount_out: u64, // Least output the user accepts
+    pub tip: u64, // Input tokens paid to the relayer on top of amount_in (0 for none)
+    pub nonce: u64, // Must match the user's intent nonce
+    pub expiry: i64, // Unix timestamp after which the intent can't execute
+}
+
+impl<'info> ExecuteIntent<'info> {
+    // Check the intent's signature, nonce and expiry, then execute it like a regular swap
+    pub fn execute(&mut self, intent: SwapIntent, bumps: ExecuteIntentBumps) -> Result<()> {
+        // Check that the pool is not locked
+        require!(!self.config.locked, AmmError::PoolLocked);
+        self.config.check_direct_trading()?;
+        require!(intent.amount_in > 0, AmmError::InvalidAmount);
+
+        // Launches only trade through `swap`, which stops them at the raise target, so they can't be traded through an intent
+        require!(self.config.curve != CurveType::BondingCurve, AmmError::LaunchNotGraduated);
+
+        // The intent must be for this pool and user, and still live
+        require_keys_eq!(intent.pool, self.config.key(), AmmError::InvalidSignature);
+        require_keys_eq!(intent.user, self.user.key(), AmmError::InvalidSignature);
+        require!(Clock::get()?.unix_timestamp <= intent.expiry, AmmError::IntentExpired);
+
+        // The user must have signed exactly this intent
+        let mut message = Vec::new();
+        intent.serialize(&mut message)?;
+        signature::verify_previous_ed25519(&self.instructions.to_account_info(), &self.user.key(), &message)?;
+
+        // Consume the nonce so the intent can't be replayed
+        if self.nonce.user == Pubkey::default() {
+            self.nonce.user = self.user.key();
+            self.nonce.nonce_bump = bumps.nonce;
+        }
+        require!(intent.nonce == self.nonce.nonce, AmmError::InvalidNonce);
+        self.nonce.nonce = self.nonce.nonce.checked_add(1).ok_or(AmmError::Overflow)?;
+
+        // Read the oracle price if the pool is anchored to one
+        let price = self.config.oracle_price(
+            self.price_feed.as_ref(),
+            self.mint_x.decimals,
+            self.mint_y.decimals,
+        )?;
+
+        // Calculate the swap using the pool's pricing curve, exactly like a regular swap
+        let is_x = intent.is_x;
+        let res = self.config.pool_swap(is_x, intent.amount_in, price)?;
+
+        // Check that the output meets the user's minimum
+        require!(res.withdraw >= intent.min_amount_out, AmmError::SlippageExceeded);
+
+        // Verify the swap amounts are valid
+        require!(res.deposit != 0, AmmError::InvalidAmount);
+        require!(res.withdraw != 0, AmmError::InvalidAmount);
+
+        let (user_in, vault_in, relayer_in, vault_out, user_out) = match is_x {
+            true => (&self.user_x, &self.vault_x, &self.relayer_x, &self.vault_y, &self.user_y),
+            false => (&self.user_y, &self.vault_y, &self.relayer_y, &self.vault_x, &self.user_x),
+        };
+
+        // Pull the input (and tip) through the delegation, and send the output to the user
+        self.config_transfer(user_in.to_account_info(), vault_in.to_account_info(), res.deposit)?;
+        self.config_transfer(vault_out.to_account_info(), user_out.to_account_info(), res.withdraw)?;
+        if intent.tip != 0 {
+            self.config_transfer(user_in.to_account_info(), relayer_in.to_account_info(), intent.tip)?;
+        }
+
+        Ok(())
+    }
+
+    // Helper function to transfer tokens with the config as authority
+    // It owns the vaults and is the delegate on the user's input account
+    pub fn config_transfer(&self, from: AccountInfo<'info>, to: AccountInfo<'info>, amount: u64) -> Result<()> {
+        let cpi_program = self.token_program.to_account_info();
+
+        let accounts = Transfer {
+            from,
+            to,
+            authority: self.config.to_account_info(),
+        };
+
+        // Create the authority seeds for the config account
+        let seeds = &[
+            &b"config"[..],
+            &self.config.seed.to_le_bytes(),
+            &[self.config.config_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, accounts, signer_seeds);
+
+        transfer(cpi_ctx, amount)
+    }
+}
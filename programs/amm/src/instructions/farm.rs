@@ -0,0 +1,420 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{associated_token::AssociatedToken, token::{transfer, Mint, Token, TokenAccount, Transfer}};
+
+use crate::{errors::AmmError, state::{Config, Farm, FarmReward, StakePosition, MAX_FARM_REWARDS}};
+
+// This struct defines all the accounts needed for the pool authority to open an LP farm
+#[derive(Accounts)]
+pub struct CreateFarm<'info> {
+    // The pool authority (pays for the accounts)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    // Pool configuration - only its authority can open a farm
+    #[account(
+        constraint = config.authority == Some(authority.key()) @ AmmError::InvalidAuthority,
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    // LP token mint of the pool - the token staked in the farm
+    #[account(
+        seeds = [b"lp", config.key().as_ref()],
+        bump = config.lp_bump,
+    )]
+    pub mint_lp: Account<'info, Mint>,
+
+    // The new farm account
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"farm", config.key().as_ref()],
+        bump,
+        space = Farm::INIT_SPACE,
+    )]
+    pub farm: Account<'info, Farm>,
+
+    // Farm's LP token account - where staked LP tokens are held
+    #[account(
+        init,
+        payer = authority,
+        associated_token::mint = mint_lp,
+        associated_token::authority = farm,
+    )]
+    pub farm_lp: Account<'info, TokenAccount>,
+
+    // Required program accounts
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CreateFarm<'info> {
+    // Open an empty farm, reward tokens are added separately
+    pub fn create_farm(&mut self, bumps: CreateFarmBumps) -> Result<()> {
+        self.farm.set_inner(Farm {
+            config: self.config.key(),
+            total_staked: 0,
+            last_update: Clock::get()?.unix_timestamp,
+            rewards: Vec::new(),
+            farm_bump: bumps.farm,
+        });
+
+        Ok(())
+    }
+}
+
+// This struct defines all the accounts needed for the pool authority to add a reward token to a farm
+#[derive(Accounts)]
+pub struct AddFarmReward<'info> {
+    // The pool authority (pays for the reward token account if needed)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    // Pool configuration - only its authority can change the farm
+    #[account(
+        constraint = config.authority == Some(authority.key()) @ AmmError::InvalidAuthority,
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    // The pool's farm
+    #[account(
+        mut,
+        has_one = config,
+        seeds = [b"farm", config.key().as_ref()],
+        bump = farm.farm_bump,
+    )]
+    pub farm: Account<'info, Farm>,
+
+    // The new reward token
+    pub reward_mint: Account<'info, Mint>,
+
+    // Farm's reward token account - where funded rewards are held until claimed
+    #[account(
+        init_if_needed,
+        payer = authority,
+        associated_token::mint = reward_mint,
+        associated_token::authority = farm,
+    )]
+    pub farm_reward: Account<'info, TokenAccount>,
+
+    // Required program accounts
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> AddFarmReward<'info> {
+    // Start emitting `reward_mint` at `emission_rate` per second once it's funded
+    pub fn add_reward(&mut self, emission_rate: u64) -> Result<()> {
+        require!(self.farm.rewards.len() < MAX_FARM_REWARDS, AmmError::InvalidAmount);
+        require!(self.farm.reward_index(self.reward_mint.key()).is_err(), AmmError::InvalidToken);
+
+        // Emit the other rewards up to now, so the new one starts from here
+        self.farm.update(Clock::get()?.unix_timestamp)?;
+
+        self.farm.rewards.push(FarmReward {
+            mint: self.reward_mint.key(),
+            emission_rate,
+            reward_per_share: 0,
+            remaining: 0,
+        });
+
+        Ok(())
+    }
+}
+
+// This struct defines all the accounts needed to fund one of a farm's rewards
+// Anyone can fund a farm
+#[derive(Accounts)]
+pub struct FundRewards<'info> {
+    // Whoever funds the rewards
+    pub funder: Signer<'info>,
+
+    // The farm being funded
+    #[account(
+        mut,
+        seeds = [b"farm", farm.config.as_ref()],
+        bump = farm.farm_bump,
+    )]
+    pub farm: Account<'info, Farm>,
+
+    // The reward token being funded
+    pub reward_mint: Account<'info, Mint>,
+
+    // Funder's reward token account - where the rewards come from
+    #[account(
+        mut,
+        associated_token::mint = reward_mint,
+        associated_token::authority = funder,
+    )]
+    pub funder_reward: Account<'info, TokenAccount>,
+
+    // Farm's reward token account - where funded rewards are held until claimed
+    #[account(
+        mut,
+        associated_token::mint = reward_mint,
+        associated_token::authority = farm,
+    )]
+    pub farm_reward: Account<'info, TokenAccount>,
+
+    // Required program accounts
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> FundRewards<'info> {
+    // Add `amount` reward tokens to be emitted at the reward's rate
+    pub fn fund_rewards(&mut self, amount: u64) -> Result<()> {
+        require!(amount > 0, AmmError::InvalidAmount);
+        let index = self.farm.reward_index(self.reward_mint.key())?;
+
+        // Emit up to now first, so the new funding isn't paid out for time already passed
+        self.farm.update(Clock::get()?.unix_timestamp)?;
+
+        let reward = &mut self.farm.rewards[index];
+        reward.remaining = reward.remaining.checked_add(amount).ok_or(AmmError::Overflow)?;
+
+        let cpi_program = self.token_program.to_account_info();
+
+        let accounts = Transfer {
+            from: self.funder_reward.to_account_info(),
+            to: self.farm_reward.to_account_info(),
+            authority: self.funder.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(cpi_program, accounts);
+
+        transfer(cpi_ctx, amount)
+    }
+}
+
+// This struct defines all the accounts needed to stake or unstake LP tokens in a farm
+#[derive(Accounts)]
+pub struct Stake<'info> {
+    // The LP holder staking their tokens (pays for the position account)
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    // Pool configuration - identifies the LP token
+    #[account(
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    // LP token mint of the pool
+    #[account(
+        seeds = [b"lp", config.key().as_ref()],
+        bump = config.lp_bump,
+    )]
+    pub mint_lp: Account<'info, Mint>,
+
+    // The pool's farm
+    #[account(
+        mut,
+        has_one = config,
+        seeds = [b"farm", config.key().as_ref()],
+        bump = farm.farm_bump,
+    )]
+    pub farm: Account<'info, Farm>,
+
+    // Owner's LP token account - where staked tokens come from and unstaked tokens go
+    #[account(
+        mut,
+        associated_token::mint = mint_lp,
+        associated_token::authority = owner,
+    )]
+    pub owner_lp: Account<'info, TokenAccount>,
+
+    // Farm's LP token account - where staked LP tokens are held
+    #[account(
+        mut,
+        associated_token::mint = mint_lp,
+        associated_token::authority = farm,
+    )]
+    pub farm_lp: Account<'info, TokenAccount>,
+
+    // Owner's stake in the farm, created with their first stake
+    #[account(
+        init_if_needed,
+        payer = owner,
+        seeds = [b"stake", farm.key().as_ref(), owner.key().as_ref()],
+        bump,
+        space = StakePosition::INIT_SPACE,
+    )]
+    pub position: Account<'info, StakePosition>,
+
+    // Required program accounts
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> Stake<'info> {
+    // Stake `amount` LP tokens, settling what the position earned so far
+    pub fn stake(&mut self, amount: u64, bumps: StakeBumps) -> Result<()> {
+        require!(amount > 0, AmmError::InvalidAmount);
+
+        if self.position.owner == Pubkey::default() {
+            self.position.owner = self.owner.key();
+            self.position.farm = self.farm.key();
+            self.position.stake_bump = bumps.position;
+        }
+
+        self.farm.update(Clock::get()?.unix_timestamp)?;
+        self.position.settle(&self.farm)?;
+
+        self.position.amount = self.position.amount.checked_add(amount).ok_or(AmmError::Overflow)?;
+        self.farm.total_staked = self.farm.total_staked.checked_add(amount).ok_or(AmmError::Overflow)?;
+
+        let cpi_program = self.token_program.to_account_info();
+
+        let accounts = Transfer {
+            from: self.owner_lp.to_account_info(),
+            to: self.farm_lp.to_account_info(),
+            authority: self.owner.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(cpi_program, accounts);
+
+        transfer(cpi_ctx, amount)
+    }
+
+    // Unstake `amount` LP tokens, settling what the position earned so far
+    // Earned rewards stay owed until claimed
+    pub fn unstake(&mut self, amount: u64) -> Result<()> {
+        require!(amount > 0, AmmError::InvalidAmount);
+        require!(amount <= self.position.amount, AmmError::InsufficientBalance);
+
+        self.farm.update(Clock::get()?.unix_timestamp)?;
+        self.position.settle(&self.farm)?;
+
+        self.position.amount -= amount;
+        self.farm.total_staked = self.farm.total_staked.checked_sub(amount).ok_or(AmmError::Underflow)?;
+
+        farm_transfer(
+            &self.farm,
+            self.token_program.to_account_info(),
+            self.farm_lp.to_account_info(),
+            self.owner_lp.to_account_info(),
+            amount,
+        )
+    }
+
+    // Unstake everything without updating the farm, giving up any rewards owed
+    // A way out for stakers if the farm's reward accounting ever stops updating
+    pub fn emergency_unstake(&mut self) -> Result<()> {
+        let amount = self.position.forfeit(&mut self.farm)?;
+
+        farm_transfer(
+            &self.farm,
+            self.token_program.to_account_info(),
+            self.farm_lp.to_account_info(),
+            self.owner_lp.to_account_info(),
+            amount,
+        )
+    }
+}
+
+// This struct defines all the accounts needed to claim one of a farm's rewards
+#[derive(Accounts)]
+pub struct ClaimRewards<'info> {
+    // The staker claiming their rewards (pays for their reward token account if needed)
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    // The farm paying the rewards
+    #[account(
+        mut,
+        seeds = [b"farm", farm.config.as_ref()],
+        bump = farm.farm_bump,
+    )]
+    pub farm: Account<'info, Farm>,
+
+    // Owner's stake in the farm
+    #[account(
+        mut,
+        has_one = owner,
+        has_one = farm,
+        seeds = [b"stake", farm.key().as_ref(), owner.key().as_ref()],
+        bump = position.stake_bump,
+    )]
+    pub position: Account<'info, StakePosition>,
+
+    // The reward token being claimed
+    pub reward_mint: Account<'info, Mint>,
+
+    // Farm's reward token account - where funded rewards are held until claimed
+    #[account(
+        mut,
+        associated_token::mint = reward_mint,
+        associated_token::authority = farm,
+    )]
+    pub farm_reward: Account<'info, TokenAccount>,
+
+    // Owner's reward token account - where the claimed rewards go
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = reward_mint,
+        associated_token::authority = owner,
+    )]
+    pub owner_reward: Account<'info, TokenAccount>,
+
+    // Required program accounts
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ClaimRewards<'info> {
+    // Pay out everything the position has earned of this reward
+    pub fn claim(&mut self) -> Result<()> {
+        let index = self.farm.reward_index(self.reward_mint.key())?;
+
+        self.farm.update(Clock::get()?.unix_timestamp)?;
+        self.position.settle(&self.farm)?;
+
+        let amount = self.position.owed[index];
+        require!(amount > 0, AmmError::InvalidAmount);
+        self.position.owed[index] = 0;
+
+        farm_transfer(
+            &self.farm,
+            self.token_program.to_account_info(),
+            self.farm_reward.to_account_info(),
+            self.owner_reward.to_account_info(),
+            amount,
+        )
+    }
+}
+
+// Transfer tokens out of one of the farm's token accounts, with the farm as authority
+pub fn farm_transfer<'info>(
+    farm: &Account<'info, Farm>,
+    token_program: AccountInfo<'info>,
+    from: AccountInfo<'info>,
+    to: AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    let accounts = Transfer {
+        from,
+        to,
+        authority: farm.to_account_info(),
+    };
+
+    // Create the authority seeds for the farm account
+    let seeds = &[
+        &b"farm"[..],
+        farm.config.as_ref(),
+        &[farm.farm_bump],
+    ];
+    let signer_seeds = &[&seeds[..]];
+
+    let cpi_ctx = CpiContext::new_with_signer(token_program, accounts, signer_seeds);
+
+    transfer(cpi_ctx, amount)
+}
@@ -0,0 +1,198 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{
+        instruction::{get_stack_height, TRANSACTION_LEVEL_STACK_HEIGHT},
+        sysvar::instructions::{self, load_current_index_checked, load_instruction_at_checked},
+    },
+    Discriminator,
+};
+use anchor_spl::{associated_token::AssociatedToken, token::{transfer, Mint, Token, TokenAccount, Transfer}};
+
+use crate::{errors::AmmError, math, state::Config};
+
+// This struct defines all the accounts needed to borrow from a pool vault and repay it
+// The same accounts are used by `flash_borrow` and the matching `flash_repay`
+#[derive(Accounts)]
+pub struct FlashLoan<'info> {
+    // The user borrowing (or repaying) the tokens
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // The two tokens that can be borrowed from this pool
+    pub mint_x: Account<'info, Mint>,
+    pub mint_y: Account<'info, Mint>,
+
+    // User's token accounts - where the loan is sent and repaid from
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint_x,
+        associated_token::authority = user,
+    )]
+    pub user_x: Account<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint_y,
+        associated_token::authority = user,
+    )]
+    pub user_y: Account<'info, TokenAccount>,
+
+    // Pool vaults - where the AMM stores the tokens
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = config,
+    )]
+    pub vault_x: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = config,
+    )]
+    pub vault_y: Account<'info, TokenAccount>,
+
+    // Pool configuration - tracks the loan in progress
+    #[account(
+        mut,
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    /// CHECK: The instructions sysvar, used to find the matching repay instruction
+    #[account(address = instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    // Required program accounts
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> FlashLoan<'info> {
+    // Lend `amount` from one of the vaults, as long as a `flash_repay` for this pool
+    // comes later in the same transaction
+    pub fn borrow(&mut self, is_x: bool, amount: u64) -> Result<()> {
+        // Check that the pool is not locked (this also rules out a second loan before repaying)
+        require!(!self.config.locked, AmmError::PoolLocked);
+        require!(amount > 0, AmmError::InvalidAmount);
+
+        // Introspection only sees top-level instructions, so the borrow must be one too
+        require!(get_stack_height() == TRANSACTION_LEVEL_STACK_HEIGHT, AmmError::FlashLoanCpi);
+        self.check_repay_follows()?;
+
+        self.config.start_flash_loan(is_x, amount)?;
+        self.withdraw_tokens(is_x, amount)
+    }
+
+    // Repay the loan in progress with its fee, which stays in the vault for LPs
+    pub fn repay(&mut self) -> Result<()> {
+        let (is_x, owed) = self.config.repay_flash_loan()?;
+        self.deposit_tokens(is_x, owed)
+    }
+
+    // Check that a later instruction in this transaction is a `flash_repay` for this pool
+    pub fn check_repay_follows(&self) -> Result<()> {
+        let sysvar = self.instructions.to_account_info();
+        let current = load_current_index_checked(&sysvar)? as usize;
+
+        // Both instructions share the same accounts, so the config sits at the same position
+        let config = self.config.key();
+        let position = load_instruction_at_checked(current, &sysvar)?
+            .accounts
+            .iter()
+            .position(|meta| meta.pubkey == config)
+            .ok_or(AmmError::InvalidAccountList)?;
+
+        let mut index = current + 1;
+        while let Ok(ix) = load_instruction_at_checked(index, &sysvar) {
+            if ix.program_id == crate::ID
+                && ix.data.starts_with(crate::instruction::FlashRepay::DISCRIMINATOR)
+                && ix.accounts.get(position).is_some_and(|meta| meta.pubkey == config)
+            {
+                return Ok(());
+            }
+            index += 1;
+        }
+
+        err!(AmmError::FlashLoanNotRepaid)
+    }
+
+    // Helper function to transfer tokens from user to pool vault
+    pub fn deposit_tokens(&self, is_x: bool, amount: u64) -> Result<()> {
+        let (from, to) = match is_x {
+            true => (self.user_x.to_account_info(), self.vault_x.to_account_info()),
+            false => (self.user_y.to_account_info(), self.vault_y.to_account_info()),
+        };
+
+        let cpi_program = self.token_program.to_account_info();
+
+        let accounts = Transfer {
+            from,
+            to,
+            authority: self.user.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(cpi_program, accounts);
+
+        transfer(cpi_ctx, amount)
+    }
+
+    // Helper function to transfer tokens from pool vault to user
+    pub fn withdraw_tokens(&self, is_x: bool, amount: u64) -> Result<()> {
+        let (from, to) = match is_x {
+            true => (self.vault_x.to_account_info(), self.user_x.to_account_info()),
+            false => (self.vault_y.to_account_info(), self.user_y.to_account_info()),
+        };
+
+        let cpi_program = self.token_program.to_account_info();
+
+        let accounts = Transfer {
+            from,
+            to,
+            authority: self.config.to_account_info(),
+        };
+
+        // Create the authority seeds for the config account
+        let seeds = &[
+            &b"config"[..],
+            &self.config.seed.to_le_bytes(),
+            &[self.config.config_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, accounts, signer_seeds);
+
+        transfer(cpi_ctx, amount)
+    }
+}
+
+// This struct defines the accounts needed for the pool authority to change the flash loan fee
+#[derive(Accounts)]
+pub struct SetFlashFee<'info> {
+    // The pool authority
+    pub authority: Signer<'info>,
+
+    // Pool configuration - stores the flash loan fee
+    #[account(
+        mut,
+        constraint = config.authority == Some(authority.key()) @ AmmError::InvalidAuthority,
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+impl<'info> SetFlashFee<'info> {
+    // Set the flash loan fee in basis points
+    pub fn set_flash_fee(&mut self, fee: u16) -> Result<()> {
+        require!(fee as u64 <= math::FEE_DENOMINATOR, AmmError::InvalidFee);
+
+        self.config.flash_fee = fee;
+
+        Ok(())
+    }
+}
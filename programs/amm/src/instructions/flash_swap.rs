@@ -0,0 +1,207 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{instruction::{AccountMeta, Instruction}, program::invoke},
+};
+use anchor_spl::{associated_token::AssociatedToken, token::{transfer, Mint, Token, TokenAccount, Transfer}};
+
+use crate::{errors::AmmError, state::{Config, CurveType, PriceFeed}};
+
+// This struct defines all the accounts needed for a flash swap
+// The output is sent first, then the callback program is invoked and must pay the input
+// Remaining accounts are forwarded to the callback program as-is
+#[derive(Accounts)]
+pub struct FlashSwap<'info> {
+    // The user receiving the output (pays for the transaction)
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // The two tokens that can be traded in this pool
+    pub mint_x: Account<'info, Mint>,
+    pub mint_y: Account<'info, Mint>,
+
+    // User's token accounts - the output is sent here
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint_x,
+        associated_token::authority = user,
+    )]
+    pub user_x: Account<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint_y,
+        associated_token::authority = user,
+    )]
+    pub user_y: Account<'info, TokenAccount>,
+
+    // Pool vaults - where the AMM stores the tokens
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = config,
+    )]
+    pub vault_x: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = config,
+    )]
+    pub vault_y: Account<'info, TokenAccount>,
+
+    // Pool configuration - contains all the pool settings
+    #[account(
+        mut,
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    // Price feed - only needed for oracle-anchored pools
+    pub price_feed: Option<Account<'info, PriceFeed>>,
+
+    /// CHECK: Any program chosen by the caller; it only gets the accounts the caller passes
+    #[account(executable)]
+    pub callback_program: UncheckedAccount<'info>,
+
+    // Required program accounts
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+// Swap details appended to the caller's data when invoking the callback program
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct FlashSwapDetails {
+    pub pool: Pubkey, // Config of the pool being traded
+    pub is_x: bool, // Whether the input is token X
+    pub amount_in: u64, // Amount the callback must pay into the input vault
+    pub amount_out: u64, // Amount already sent to the user
+    pub fee: u64, // Part of amount_in kept by the pool as a fee
+}
+
+impl<'info> FlashSwap<'info> {
+    // Main flash swap function - pays out first, then checks the input arrived during the callback
+    pub fn flash_swap(&mut self, is_x: bool, amount: u64, min: u64, data: Vec<u8>, remaining_accounts: &'info [AccountInfo<'info>]) -> Result<()> {
+        // Check that the pool is not locked
+        require!(!self.config.locked, AmmError::PoolLocked);
+        self.config.check_direct_trading()?;
+        require!(amount > 0, AmmError::InvalidAmount);
+
+        // Launches only trade through `swap`, which stops them at the raise target, so they can't be flash swapped
+        require!(self.config.curve != CurveType::BondingCurve, AmmError::LaunchNotGraduated);
+
+        // The callback can't be this program, which would re-enter the pool
+        require_keys_neq!(self.callback_program.key(), crate::ID, AmmError::InvalidCallbackProgram);
+
+        // Read the oracle price if the pool is anchored to one
+        let price = self.config.oracle_price(
+            self.price_feed.as_ref(),
+            self.mint_x.decimals,
+            self.mint_y.decimals,
+        )?;
+
+        // Calculate the swap using the pool's pricing curve, exactly like a regular swap
+        let res = self.config.pool_swap(is_x, amount, price)?;
+
+        // Check that the output meets the user's minimum
+        require!(res.withdraw >= min, AmmError::SlippageExceeded);
+
+        // Verify the swap amounts are valid
+        require!(res.deposit != 0, AmmError::InvalidAmount);
+        require!(res.withdraw != 0, AmmError::InvalidAmount);
+
+        let balance_in = match is_x {
+            true => self.vault_x.amount,
+            false => self.vault_y.amount,
+        };
+
+        // Send the output before anything is paid
+        self.withdraw_tokens(is_x, res.withdraw)?;
+
+        // Lock the pool while the callback runs so it can't trade against the
+        // half-settled reserves, and persist the lock so re-entering calls see it
+        self.config.locked = true;
+        self.config.exit(&crate::ID)?;
+
+        let details = FlashSwapDetails {
+            pool: self.config.key(),
+            is_x,
+            amount_in: res.deposit,
+            amount_out: res.withdraw,
+            fee: res.fee,
+        };
+        self.invoke_callback(data, details, remaining_accounts)?;
+
+        // The input vault must have received the full input, which restores the curve's
+        // fee-adjusted invariant exactly as a regular swap would
+        let vault_in = match is_x {
+            true => &mut self.vault_x,
+            false => &mut self.vault_y,
+        };
+        vault_in.reload()?;
+        let expected = balance_in.checked_add(res.deposit).ok_or(AmmError::Overflow)?;
+        require!(vault_in.amount >= expected, AmmError::FlashSwapNotPaid);
+
+        self.config.locked = false;
+
+        Ok(())
+    }
+
+    // Invoke the callback program with the caller's data followed by the swap details
+    pub fn invoke_callback(&self, mut data: Vec<u8>, details: FlashSwapDetails, remaining_accounts: &'info [AccountInfo<'info>]) -> Result<()> {
+        details.serialize(&mut data)?;
+
+        let accounts = remaining_accounts
+            .iter()
+            .map(|info| AccountMeta {
+                pubkey: info.key(),
+                is_signer: info.is_signer,
+                is_writable: info.is_writable,
+            })
+            .collect();
+
+        let ix = Instruction {
+            program_id: self.callback_program.key(),
+            accounts,
+            data,
+        };
+
+        let mut infos = remaining_accounts.to_vec();
+        infos.push(self.callback_program.to_account_info());
+
+        invoke(&ix, &infos)?;
+
+        Ok(())
+    }
+
+    // Helper function to transfer the output from pool vault to user
+    pub fn withdraw_tokens(&self, is_x: bool, amount: u64) -> Result<()> {
+        let (from, to) = match is_x {
+            true => (self.vault_y.to_account_info(), self.user_y.to_account_info()),
+            false => (self.vault_x.to_account_info(), self.user_x.to_account_info()),
+        };
+
+        let cpi_program = self.token_program.to_account_info();
+
+        let accounts = Transfer {
+            from,
+            to,
+            authority: self.config.to_account_info(),
+        };
+
+        // Create the authority seeds for the config account
+        let seeds = &[
+            &b"config"[..],
+            &self.config.seed.to_le_bytes(),
+            &[self.config.config_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, accounts, signer_seeds);
+
+        transfer(cpi_ctx, amount)
+    }
+}
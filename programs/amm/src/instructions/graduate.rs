@@ -0,0 +1,139 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{associated_token::AssociatedToken, token::{mint_to, transfer, Mint, MintTo, Token, TokenAccount, Transfer}};
+
+use crate::{errors::AmmError, math, state::{Config, CurveType}};
+
+// This struct defines all the accounts needed to graduate a launch into a constant product pool
+// Anyone can call it once the launch has hit its raise target
+#[derive(Accounts)]
+pub struct Graduate<'info> {
+    // The caller (pays for the lock account if needed)
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    // The token sold by the launch
+    pub mint_x: Account<'info, Mint>,
+
+    // Pool configuration - contains all the pool settings
+    #[account(
+        mut,
+        has_one = mint_x,
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    // LP token mint - the whole pool's LP is minted into the lock account
+    #[account(
+        mut,
+        seeds = [b"lp", config.key().as_ref()],
+        bump = config.lp_bump,
+    )]
+    pub mint_lp: Account<'info, Mint>,
+
+    // Pool vault for X - the unsold X the pool doesn't need comes out of it
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = config,
+    )]
+    pub vault_x: Account<'info, TokenAccount>,
+
+    /// CHECK: The pool's lock PDA, which never signs, so it holds no data and only owns the lock token account
+    #[account(address = Config::lock_authority(&config.key()) @ AmmError::InvalidAccountList)]
+    pub lock_authority: UncheckedAccount<'info>,
+
+    // Lock PDA's LP token account - where the pool's LP is locked forever
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = mint_lp,
+        associated_token::authority = lock_authority,
+    )]
+    pub lock_lp: Account<'info, TokenAccount>,
+
+    // Launch creator's X account - gets back the unsold X the pool doesn't need
+    #[account(
+        mut,
+        token::mint = mint_x,
+        constraint = config.authority == Some(authority_x.owner) @ AmmError::InvalidAuthority,
+    )]
+    pub authority_x: Account<'info, TokenAccount>,
+
+    // Required program accounts
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> Graduate<'info> {
+    // Migrate a launch's reserves into a constant product pool with permanently locked LP,
+    // returning the X it doesn't need to the launch creator
+    pub fn graduate(&mut self) -> Result<()> {
+        require!(self.config.should_graduate(self.config.reserve_y), AmmError::LaunchNotGraduated);
+
+        let (reserve_x, liquidity) = math::graduation_liquidity(self.config.reserve_x, self.config.reserve_y, self.config.virtual_y)?;
+
+        let excess_x = self.config.reserve_x - reserve_x;
+        if excess_x != 0 {
+            self.config.record_withdraw(excess_x, 0)?;
+            self.return_excess_x(excess_x)?;
+        }
+
+        // Mint LP for the whole pool into the lock account
+        self.mint_locked_lp(liquidity)?;
+        self.config.burned_lp = self.config.burned_lp.checked_add(liquidity).ok_or(AmmError::Overflow)?;
+        self.config.record_lp_minted(liquidity)?;
+
+        self.config.curve = CurveType::ConstantProduct;
+        self.config.virtual_y = 0;
+
+        Ok(())
+    }
+
+    // Helper function to send the unneeded X back to the launch creator
+    pub fn return_excess_x(&self, amount: u64) -> Result<()> {
+        let cpi_program = self.token_program.to_account_info();
+
+        let accounts = Transfer {
+            from: self.vault_x.to_account_info(),
+            to: self.authority_x.to_account_info(),
+            authority: self.config.to_account_info(),
+        };
+
+        // Create the authority seeds for the config account
+        let seeds = &[
+            &b"config"[..],
+            &self.config.seed.to_le_bytes(),
+            &[self.config.config_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, accounts, signer_seeds);
+
+        transfer(cpi_ctx, amount)
+    }
+
+    // Helper function to mint LP tokens into the lock account
+    pub fn mint_locked_lp(&self, amount: u64) -> Result<()> {
+        let cpi_program = self.token_program.to_account_info();
+
+        let accounts = MintTo {
+            mint: self.mint_lp.to_account_info(),
+            to: self.lock_lp.to_account_info(),
+            authority: self.config.to_account_info(),
+        };
+
+        // Create the authority seeds for the config account
+        let seeds = &[
+            &b"config"[..],
+            &self.config.seed.to_le_bytes(),
+            &[self.config.config_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, accounts, signer_seeds);
+
+        mint_to(cpi_ctx, amount)
+    }
+}
@@ -1,7 +1,7 @@
 use anchor_lang::prelude::*;
-use anchor_spl::{associated_token::AssociatedToken, token::{Mint, Token, TokenAccount}};
+use anchor_spl::{associated_token::AssociatedToken, token::{transfer, Mint, Token, TokenAccount, Transfer}};
 
-use crate::state::Config;
+use crate::{errors::AmmError, math::weighted::{MAX_SCHEDULE_DURATION, MAX_WEIGHT, MIN_WEIGHT}, state::{Config, CurveType}};
 
 // This struct defines all the accounts needed to initialize an AMM
 // Each field represents an account that must be provided when calling initialize
@@ -57,6 +57,14 @@ pub struct Initialize <'info> {
         space = Config::INIT_SPACE,
     )]
     pub config: Account<'info, Config>,
+
+    // Initializer's X account - only needed to fund a launch with the X it sells
+    #[account(
+        mut,
+        token::mint = mint_x,
+        token::authority = initializer,
+    )]
+    pub initializer_x: Option<Account<'info, TokenAccount>>,
     
     // Required program accounts
     pub token_program: Program<'info, Token>,
@@ -68,17 +76,103 @@ impl<'info> Initialize<'info> {
     // Initialize the AMM with the provided parameters
     pub fn init(&mut self, seed: u64, fee: u16, authority: Option<Pubkey>, bumps: InitializeBumps) -> Result<()> {
         // Store all the configuration data in the config account
-        self.config.set_inner(Config {
+        self.config.set_inner(Config::new(
             seed,
             authority,
-            mint_x: self.mint_x.key(),
-            mint_y: self.mint_y.key(),
+            self.mint_x.key(),
+            self.mint_y.key(),
             fee,
-            locked: false, // Pool starts unlocked
-            config_bump: bumps.config,
-            lp_bump: bumps.mint_lp,
-        });
+            bumps.config,
+            bumps.mint_lp,
+        ));
+
+        Ok(())
+    }
+
+    // Turn the freshly initialized pool into an oracle-anchored (PMM) pool
+    pub fn init_oracle(&mut self, params: OracleParams) -> Result<()> {
+        require!(params.max_confidence <= 10_000, AmmError::InvalidAmount);
+        require!(params.k <= 10_000, AmmError::InvalidAmount);
+
+        self.config.curve = CurveType::Oracle;
+        self.config.oracle = Some(params.price_feed);
+        self.config.max_price_age = params.max_price_age;
+        self.config.max_confidence = params.max_confidence;
+        self.config.pmm_k = params.k;
+
+        Ok(())
+    }
+
+    // Turn the freshly initialized pool into a weighted liquidity bootstrapping pool
+    pub fn init_lbp(&mut self, params: LbpParams) -> Result<()> {
+        // Only the authority can provide liquidity during the sale, so one must be set
+        require!(self.config.authority.is_some(), AmmError::NoAuthoritySet);
+        require!((MIN_WEIGHT..=MAX_WEIGHT).contains(&params.start_weight_x), AmmError::InvalidAmount);
+        require!((MIN_WEIGHT..=MAX_WEIGHT).contains(&params.end_weight_x), AmmError::InvalidAmount);
+        // A bounded schedule keeps the weight math well away from i64 overflow
+        require!(params.start_time >= 0, AmmError::InvalidAmount);
+        require!(params.end_time >= params.start_time, AmmError::InvalidAmount);
+        require!(params.end_time - params.start_time <= MAX_SCHEDULE_DURATION, AmmError::InvalidAmount);
+
+        self.config.curve = CurveType::Weighted;
+        self.config.start_weight_x = params.start_weight_x;
+        self.config.end_weight_x = params.end_weight_x;
+        self.config.start_time = params.start_time;
+        self.config.end_time = params.end_time;
 
         Ok(())
     }
-}
\ No newline at end of file
+
+    // Turn the freshly initialized pool into a bonding curve launch
+    pub fn init_launch(&mut self, params: LaunchParams) -> Result<()> {
+        // The authority gets back the unsold X when the launch graduates, so one must be set
+        require!(self.config.authority.is_some(), AmmError::NoAuthoritySet);
+        require!(params.supply != 0, AmmError::InvalidAmount);
+        require!(params.virtual_y != 0, AmmError::InvalidAmount);
+        require!(params.raise_target != 0, AmmError::InvalidAmount);
+
+        self.config.curve = CurveType::BondingCurve;
+        self.config.virtual_y = params.virtual_y;
+        self.config.raise_target = params.raise_target;
+
+        // Move the launch supply into the pool and count it as reserves straight away
+        let initializer_x = self.initializer_x.as_ref().ok_or(AmmError::InvalidAccountList)?;
+        let accounts = Transfer {
+            from: initializer_x.to_account_info(),
+            to: self.vault_x.to_account_info(),
+            authority: self.initializer.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(self.token_program.to_account_info(), accounts);
+        transfer(cpi_ctx, params.supply)?;
+
+        self.config.record_deposit(params.supply, 0)
+    }
+}
+
+// Settings for an oracle-anchored (PMM) pool
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct OracleParams {
+    pub price_feed: Pubkey, // Price feed account quoting X in Y
+    pub max_price_age: u64, // Oldest price (in seconds) the pool will trade against
+    pub max_confidence: u16, // Widest confidence interval, in basis points of the price
+    pub k: u16, // Slippage in basis points (0 = fixed oracle price, 10_000 = constant product)
+}
+
+// Settings for a liquidity bootstrapping pool
+// Weights move linearly from the start weights to the end weights over the sale window
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct LbpParams {
+    pub start_weight_x: u16, // Weight of X in basis points at the start (e.g. 9_500 = 95/5)
+    pub end_weight_x: u16, // Weight of X in basis points at the end (e.g. 5_000 = 50/50)
+    pub start_time: i64, // Unix timestamp when the sale starts
+    pub end_time: i64, // Unix timestamp when the sale ends
+}
+
+// Settings for a bonding curve launch
+// The starting price is virtual_y / supply, and it rises as X is bought
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct LaunchParams {
+    pub supply: u64, // X sold through the curve, moved from initializer_x into vault_x
+    pub virtual_y: u64, // Virtual Y reserve used for pricing until graduation
+    pub raise_target: u64, // Amount of Y raised that graduates the launch
+}
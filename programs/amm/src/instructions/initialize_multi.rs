@@ -0,0 +1,104 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{associated_token::{create, AssociatedToken, Create}, token::{Mint, Token}};
+
+use crate::{errors::AmmError, state::{MultiPool, MAX_ASSETS}};
+
+// This struct defines all the accounts needed to initialize a multi-asset pool
+// The pool's mints and vaults are passed as remaining accounts:
+// [mint_0, vault_0, mint_1, vault_1, ...]
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+pub struct InitializeMulti<'info> {
+    // The person who's creating the pool (pays for the transaction)
+    #[account(mut)]
+    pub initializer: Signer<'info>,
+
+    // LP token mint - represents liquidity provider shares
+    #[account(
+        init,
+        payer = initializer,
+        seeds = [b"lp", pool.key.as_ref()],
+        bump,
+        mint::decimals = 6,
+        mint::authority = pool,
+    )]
+    pub mint_lp: Account<'info, Mint>,
+
+    // Pool account - stores the list of mints and pool settings
+    #[account(
+        init,
+        payer = initializer,
+        seeds = [b"multi", seed.to_le_bytes().as_ref()],
+        bump,
+        space = MultiPool::INIT_SPACE,
+    )]
+    pub pool: Account<'info, MultiPool>,
+
+    // Required program accounts
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitializeMulti<'info> {
+    // Initialize the pool and create a vault for every mint
+    pub fn init(
+        &mut self,
+        seed: u64,
+        fee: u16,
+        authority: Option<Pubkey>,
+        bumps: InitializeMultiBumps,
+        remaining_accounts: &'info [AccountInfo<'info>],
+    ) -> Result<()> {
+        require!(remaining_accounts.len().is_multiple_of(2), AmmError::InvalidAccountList);
+
+        let count = remaining_accounts.len() / 2;
+        require!((2..=MAX_ASSETS).contains(&count), AmmError::InvalidAssetCount);
+
+        let mut mints = Vec::with_capacity(count);
+
+        for accounts in remaining_accounts.chunks(2) {
+            let (mint, vault) = (&accounts[0], &accounts[1]);
+
+            // Every entry must be a real mint, and each mint can only appear once
+            Account::<Mint>::try_from(mint)?;
+            require!(!mints.contains(mint.key), AmmError::InvalidToken);
+
+            // Create the pool's associated token account for this mint
+            self.create_vault(mint, vault)?;
+
+            mints.push(mint.key());
+        }
+
+        self.pool.set_inner(MultiPool {
+            seed,
+            authority,
+            mints,
+            fee,
+            locked: false, // Pool starts unlocked
+            pool_bump: bumps.pool,
+            lp_bump: bumps.mint_lp,
+            reserves: vec![0; count],
+        });
+
+        Ok(())
+    }
+
+    // Helper function to create a vault owned by the pool
+    pub fn create_vault(&self, mint: &AccountInfo<'info>, vault: &AccountInfo<'info>) -> Result<()> {
+        let cpi_program = self.associated_token_program.to_account_info();
+
+        let cpi_accounts = Create {
+            payer: self.initializer.to_account_info(),
+            associated_token: vault.to_account_info(),
+            authority: self.pool.to_account_info(),
+            mint: mint.to_account_info(),
+            system_program: self.system_program.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+        };
+
+        let ctx = CpiContext::new(cpi_program, cpi_accounts);
+
+        create(ctx)
+    }
+}
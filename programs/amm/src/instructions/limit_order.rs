@@ -0,0 +1,385 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::{get_associated_token_address, AssociatedToken},
+    token::{close_account, transfer, CloseAccount, Mint, Token, TokenAccount, Transfer},
+};
+
+use crate::{errors::AmmError, state::{Config, CurveType, Order, PriceFeed, MAX_ORDER_BOUNTY}};
+
+use super::next_account;
+
+// This struct defines all the accounts needed to place a limit order
+// The input tokens move into an escrow owned by the order until it's filled or cancelled
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+pub struct PlaceOrder<'info> {
+    // The trader placing the order (pays for the accounts)
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    // The token being sold and the token being bought
+    pub mint_in: Account<'info, Mint>,
+    pub mint_out: Account<'info, Mint>,
+
+    // Pool the order rests against - it must trade this pair
+    #[account(
+        constraint = (config.mint_x == mint_in.key() && config.mint_y == mint_out.key())
+            || (config.mint_x == mint_out.key() && config.mint_y == mint_in.key()) @ AmmError::InvalidToken,
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    // Owner's token accounts - input is taken from one, and the output will be sent to the other
+    #[account(
+        mut,
+        associated_token::mint = mint_in,
+        associated_token::authority = owner,
+    )]
+    pub owner_in: Account<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = mint_out,
+        associated_token::authority = owner,
+    )]
+    pub owner_out: Account<'info, TokenAccount>,
+
+    // The new order account
+    #[account(
+        init,
+        payer = owner,
+        seeds = [b"order", config.key().as_ref(), owner.key().as_ref(), seed.to_le_bytes().as_ref()],
+        bump,
+        space = Order::INIT_SPACE,
+    )]
+    pub order: Account<'info, Order>,
+
+    // Escrow holding the input until the order is filled
+    #[account(
+        init,
+        payer = owner,
+        associated_token::mint = mint_in,
+        associated_token::authority = order,
+    )]
+    pub escrow: Account<'info, TokenAccount>,
+
+    // Required program accounts
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> PlaceOrder<'info> {
+    // Escrow `amount` of the input token, to be sold once it fetches at least `min_amount_out`
+    pub fn place(&mut self, seed: u64, amount: u64, min_amount_out: u64, bounty: u16, bumps: PlaceOrderBumps) -> Result<()> {
+        require!(amount > 0, AmmError::InvalidAmount);
+        require!(min_amount_out > 0, AmmError::InvalidAmount);
+        require!(bounty <= MAX_ORDER_BOUNTY, AmmError::InvalidAmount);
+
+        // Launches only trade through `swap`, which stops them at the raise target until they graduate
+        require!(self.config.curve != CurveType::BondingCurve, AmmError::LaunchNotGraduated);
+
+        self.order.set_inner(Order {
+            owner: self.owner.key(),
+            config: self.config.key(),
+            mint_in: self.mint_in.key(),
+            seed,
+            is_x: self.mint_in.key() == self.config.mint_x,
+            amount,
+            min_amount_out,
+            bounty,
+            order_bump: bumps.order,
+        });
+
+        self.deposit_tokens(amount)
+    }
+
+    // Helper function to transfer the input from owner to escrow
+    pub fn deposit_tokens(&self, amount: u64) -> Result<()> {
+        let cpi_program = self.token_program.to_account_info();
+
+        let accounts = Transfer {
+            from: self.owner_in.to_account_info(),
+            to: self.escrow.to_account_info(),
+            authority: self.owner.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(cpi_program, accounts);
+
+        transfer(cpi_ctx, amount)
+    }
+}
+
+// This struct defines all the accounts needed to cancel a limit order
+#[derive(Accounts)]
+pub struct CancelOrder<'info> {
+    // The trader who placed the order (receives the rent back)
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    // The token the order was selling
+    pub mint_in: Account<'info, Mint>,
+
+    // Owner's token account for the input - the escrow is refunded here
+    #[account(
+        mut,
+        associated_token::mint = mint_in,
+        associated_token::authority = owner,
+    )]
+    pub owner_in: Account<'info, TokenAccount>,
+
+    // The order being cancelled
+    #[account(
+        mut,
+        close = owner,
+        has_one = owner,
+        has_one = mint_in,
+        seeds = [b"order", order.config.as_ref(), owner.key().as_ref(), order.seed.to_le_bytes().as_ref()],
+        bump = order.order_bump,
+    )]
+    pub order: Account<'info, Order>,
+
+    // Escrow holding the order's input
+    #[account(
+        mut,
+        associated_token::mint = mint_in,
+        associated_token::authority = order,
+    )]
+    pub escrow: Account<'info, TokenAccount>,
+
+    // Required program accounts
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> CancelOrder<'info> {
+    // Refund the escrow and close it, the order account is closed by Anchor
+    pub fn cancel(&mut self) -> Result<()> {
+        let order = &self.order;
+
+        // Create the authority seeds for the order account
+        let seed = order.seed.to_le_bytes();
+        let seeds = &[
+            &b"order"[..],
+            order.config.as_ref(),
+            order.owner.as_ref(),
+            &seed,
+            &[order.order_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        let accounts = Transfer {
+            from: self.escrow.to_account_info(),
+            to: self.owner_in.to_account_info(),
+            authority: order.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), accounts, signer_seeds);
+        transfer(cpi_ctx, self.escrow.amount)?;
+
+        let accounts = CloseAccount {
+            account: self.escrow.to_account_info(),
+            destination: self.owner.to_account_info(),
+            authority: order.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), accounts, signer_seeds);
+
+        close_account(cpi_ctx)
+    }
+}
+
+// This struct defines all the accounts needed to crank limit orders resting against a pool
+// Anyone can call it; each order is passed as remaining accounts:
+// [order (writable), escrow (writable), owner_out (writable), owner (writable)]
+#[derive(Accounts)]
+pub struct FillOrders<'info> {
+    // Whoever cranks the orders - earns each filled order's bounty
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+
+    // The two tokens that can be traded in this pool
+    pub mint_x: Account<'info, Mint>,
+    pub mint_y: Account<'info, Mint>,
+
+    // Cranker's token accounts - bounties are paid in the output token
+    #[account(
+        init_if_needed,
+        payer = cranker,
+        associated_token::mint = mint_x,
+        associated_token::authority = cranker,
+    )]
+    pub cranker_x: Account<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = cranker,
+        associated_token::mint = mint_y,
+        associated_token::authority = cranker,
+    )]
+    pub cranker_y: Account<'info, TokenAccount>,
+
+    // Pool vaults - where the AMM stores the tokens
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = config,
+    )]
+    pub vault_x: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = config,
+    )]
+    pub vault_y: Account<'info, TokenAccount>,
+
+    // Pool configuration - contains all the pool settings
+    #[account(
+        mut,
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    // Price feed - only needed for oracle-anchored pools
+    pub price_feed: Option<Account<'info, PriceFeed>>,
+
+    // Required program accounts
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> FillOrders<'info> {
+    // Fill every passed order whose limit price the pool now meets, skipping the rest
+    pub fn fill_orders(&mut self, remaining_accounts: &'info [AccountInfo<'info>]) -> Result<()> {
+        // Check that the pool is not locked
+        require!(!self.config.locked, AmmError::PoolLocked);
+        self.config.check_direct_trading()?;
+
+        // Read the oracle price if the pool is anchored to one
+        let price = self.config.oracle_price(
+            self.price_feed.as_ref(),
+            self.mint_x.decimals,
+            self.mint_y.decimals,
+        )?;
+
+        let mut accounts = remaining_accounts.iter();
+        let mut filled = 0;
+
+        while accounts.len() != 0 {
+            let order: Account<'info, Order> = Account::try_from(next_account(&mut accounts)?)?;
+            let escrow_info = next_account(&mut accounts)?;
+            let owner_out_info = next_account(&mut accounts)?;
+            let owner_info = next_account(&mut accounts)?;
+
+            // Check the accounts belong to this order and pool
+            let mint_out = match order.is_x {
+                true => self.config.mint_y,
+                false => self.config.mint_x,
+            };
+            require_keys_eq!(order.config, self.config.key(), AmmError::InvalidAccountList);
+            require_keys_eq!(owner_info.key(), order.owner, AmmError::InvalidAccountList);
+            require_keys_eq!(escrow_info.key(), get_associated_token_address(&order.key(), &order.mint_in), AmmError::InvalidAccountList);
+            require_keys_eq!(owner_out_info.key(), get_associated_token_address(&order.owner, &mint_out), AmmError::InvalidAccountList);
+
+            // Quote on a copy, so nothing changes unless the order fills
+            let mut config = Config::clone(&self.config);
+            let Ok(res) = config.pool_swap(order.is_x, order.amount, price) else {
+                continue;
+            };
+
+            // Only fill once the output after the bounty meets the order's limit price
+            let Some((amount_out, bounty)) = order.split_output(res.withdraw)? else {
+                continue;
+            };
+
+            // Later orders are quoted against the updated reserves
+            self.config.set_inner(config);
+
+            let escrow: Account<'info, TokenAccount> = Account::try_from(escrow_info)?;
+            self.settle_order(&order, &escrow, owner_info.clone(), owner_out_info.clone(), amount_out, bounty)?;
+            order.close(owner_info.clone())?;
+
+            filled += 1;
+        }
+
+        require!(filled > 0, AmmError::NoOrdersFilled);
+
+        Ok(())
+    }
+
+    // Move the order's escrow into the pool and pay the owner and the cranker
+    pub fn settle_order(
+        &self,
+        order: &Account<'info, Order>,
+        escrow: &Account<'info, TokenAccount>,
+        owner: AccountInfo<'info>,
+        owner_out: AccountInfo<'info>,
+        amount_out: u64,
+        bounty: u64,
+    ) -> Result<()> {
+        let (vault_in, vault_out, cranker_out) = match order.is_x {
+            true => (&self.vault_x, &self.vault_y, &self.cranker_y),
+            false => (&self.vault_y, &self.vault_x, &self.cranker_x),
+        };
+
+        // Create the authority seeds for the order account
+        let seed = order.seed.to_le_bytes();
+        let seeds = &[
+            &b"order"[..],
+            order.config.as_ref(),
+            order.owner.as_ref(),
+            &seed,
+            &[order.order_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        // The whole escrow goes to the pool, so anything sent to it on top of the order
+        // is left in the vault for a sync or skim rather than blocking the close
+        let accounts = Transfer {
+            from: escrow.to_account_info(),
+            to: vault_in.to_account_info(),
+            authority: order.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), accounts, signer_seeds);
+        transfer(cpi_ctx, escrow.amount)?;
+
+        let accounts = CloseAccount {
+            account: escrow.to_account_info(),
+            destination: owner,
+            authority: order.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), accounts, signer_seeds);
+        close_account(cpi_ctx)?;
+
+        self.withdraw_tokens(vault_out.to_account_info(), owner_out, amount_out)?;
+        if bounty != 0 {
+            self.withdraw_tokens(vault_out.to_account_info(), cranker_out.to_account_info(), bounty)?;
+        }
+
+        Ok(())
+    }
+
+    // Helper function to transfer tokens out of a pool vault
+    pub fn withdraw_tokens(&self, from: AccountInfo<'info>, to: AccountInfo<'info>, amount: u64) -> Result<()> {
+        let cpi_program = self.token_program.to_account_info();
+
+        let accounts = Transfer {
+            from,
+            to,
+            authority: self.config.to_account_info(),
+        };
+
+        // Create the authority seeds for the config account
+        let seeds = &[
+            &b"config"[..],
+            &self.config.seed.to_le_bytes(),
+            &[self.config.config_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, accounts, signer_seeds);
+
+        transfer(cpi_ctx, amount)
+    }
+}
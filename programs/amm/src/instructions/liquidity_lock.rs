@@ -0,0 +1,312 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{close_account, transfer, CloseAccount, Mint, Token, TokenAccount, Transfer},
+};
+
+use crate::{errors::AmmError, state::{Config, LiquidityLock}};
+
+// This struct defines all the accounts needed to lock LP tokens until a set time
+// The LP tokens move into an escrow owned by the lock until the beneficiary unlocks them
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+pub struct LockLiquidity<'info> {
+    // The LP holder locking their tokens (pays for the accounts)
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    // Pool configuration - keeps the pool's total of time-locked LP
+    #[account(
+        mut,
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    // LP token mint of the pool
+    #[account(
+        seeds = [b"lp", config.key().as_ref()],
+        bump = config.lp_bump,
+    )]
+    pub mint_lp: Account<'info, Mint>,
+
+    // Owner's LP token account - where the locked tokens come from
+    #[account(
+        mut,
+        associated_token::mint = mint_lp,
+        associated_token::authority = owner,
+    )]
+    pub owner_lp: Account<'info, TokenAccount>,
+
+    // The new lock account
+    #[account(
+        init,
+        payer = owner,
+        seeds = [b"liquidity_lock", config.key().as_ref(), owner.key().as_ref(), seed.to_le_bytes().as_ref()],
+        bump,
+        space = LiquidityLock::INIT_SPACE,
+    )]
+    pub lock: Account<'info, LiquidityLock>,
+
+    // Escrow holding the LP tokens until they're unlocked
+    #[account(
+        init,
+        payer = owner,
+        associated_token::mint = mint_lp,
+        associated_token::authority = lock,
+    )]
+    pub escrow: Account<'info, TokenAccount>,
+
+    // Required program accounts
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> LockLiquidity<'info> {
+    // Escrow `amount` LP tokens until `unlock_time`, after which only `beneficiary` can take them out
+    pub fn lock(&mut self, seed: u64, amount: u64, unlock_time: i64, beneficiary: Pubkey, bumps: LockLiquidityBumps) -> Result<()> {
+        require!(amount > 0, AmmError::InvalidAmount);
+        require!(unlock_time > Clock::get()?.unix_timestamp, AmmError::InvalidAmount);
+
+        self.lock.set_inner(LiquidityLock {
+            owner: self.owner.key(),
+            beneficiary,
+            config: self.config.key(),
+            seed,
+            amount,
+            unlock_time,
+            lock_bump: bumps.lock,
+        });
+
+        self.config.time_locked_lp = self.config.time_locked_lp.checked_add(amount).ok_or(AmmError::Overflow)?;
+
+        self.deposit_tokens(amount)
+    }
+
+    // Helper function to transfer the LP tokens from owner to escrow
+    pub fn deposit_tokens(&self, amount: u64) -> Result<()> {
+        let cpi_program = self.token_program.to_account_info();
+
+        let accounts = Transfer {
+            from: self.owner_lp.to_account_info(),
+            to: self.escrow.to_account_info(),
+            authority: self.owner.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(cpi_program, accounts);
+
+        transfer(cpi_ctx, amount)
+    }
+}
+
+// This struct defines all the accounts needed to unlock LP tokens once their lock expires
+#[derive(Accounts)]
+pub struct UnlockLiquidity<'info> {
+    // The lock's beneficiary (pays for their LP token account if needed)
+    #[account(mut)]
+    pub beneficiary: Signer<'info>,
+
+    // Whoever created the lock - receives the rent back
+    #[account(mut)]
+    pub owner: SystemAccount<'info>,
+
+    // Pool configuration - keeps the pool's total of time-locked LP
+    #[account(
+        mut,
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    // LP token mint of the pool
+    #[account(
+        seeds = [b"lp", config.key().as_ref()],
+        bump = config.lp_bump,
+    )]
+    pub mint_lp: Account<'info, Mint>,
+
+    // Beneficiary's LP token account - where the unlocked tokens go
+    #[account(
+        init_if_needed,
+        payer = beneficiary,
+        associated_token::mint = mint_lp,
+        associated_token::authority = beneficiary,
+    )]
+    pub beneficiary_lp: Account<'info, TokenAccount>,
+
+    // The lock being released
+    #[account(
+        mut,
+        close = owner,
+        has_one = owner,
+        has_one = beneficiary,
+        has_one = config,
+        seeds = [b"liquidity_lock", config.key().as_ref(), owner.key().as_ref(), lock.seed.to_le_bytes().as_ref()],
+        bump = lock.lock_bump,
+    )]
+    pub lock: Account<'info, LiquidityLock>,
+
+    // Escrow holding the locked LP tokens
+    #[account(
+        mut,
+        associated_token::mint = mint_lp,
+        associated_token::authority = lock,
+    )]
+    pub escrow: Account<'info, TokenAccount>,
+
+    // Owner's LP token account - only needed if LP was sent to the escrow on top of the lock
+    #[account(
+        mut,
+        associated_token::mint = mint_lp,
+        associated_token::authority = owner,
+    )]
+    pub owner_lp: Option<Account<'info, TokenAccount>>,
+
+    // Required program accounts
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> UnlockLiquidity<'info> {
+    // Release the locked LP to the beneficiary, return any surplus to the owner and close the escrow,
+    // the lock account is closed by Anchor
+    pub fn unlock(&mut self) -> Result<()> {
+        let (amount, surplus) = self.lock.release(self.escrow.amount, Clock::get()?.unix_timestamp)?;
+
+        self.config.time_locked_lp = self.config.time_locked_lp.checked_sub(amount).ok_or(AmmError::Underflow)?;
+
+        let lock = &self.lock;
+
+        // Create the authority seeds for the lock account
+        let seed = lock.seed.to_le_bytes();
+        let seeds = &[
+            &b"liquidity_lock"[..],
+            lock.config.as_ref(),
+            lock.owner.as_ref(),
+            &seed,
+            &[lock.lock_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        // The beneficiary gets exactly what was locked
+        let accounts = Transfer {
+            from: self.escrow.to_account_info(),
+            to: self.beneficiary_lp.to_account_info(),
+            authority: lock.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), accounts, signer_seeds);
+        transfer(cpi_ctx, amount)?;
+
+        // Anything sent to the escrow on top of the lock goes back to the owner
+        if surplus != 0 {
+            let owner_lp = self.owner_lp.as_ref().ok_or(AmmError::InvalidAccountList)?;
+            let accounts = Transfer {
+                from: self.escrow.to_account_info(),
+                to: owner_lp.to_account_info(),
+                authority: lock.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), accounts, signer_seeds);
+            transfer(cpi_ctx, surplus)?;
+        }
+
+        let accounts = CloseAccount {
+            account: self.escrow.to_account_info(),
+            destination: self.owner.to_account_info(),
+            authority: lock.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), accounts, signer_seeds);
+
+        close_account(cpi_ctx)
+    }
+}
+
+// This struct defines all the accounts needed to lock LP tokens forever
+// The LP tokens move to the pool's lock PDA, which no instruction ever signs for
+#[derive(Accounts)]
+pub struct BurnLock<'info> {
+    // The LP holder burning their tokens (pays for the lock token account if needed)
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    // Pool configuration - keeps the pool's total of permanently locked LP
+    #[account(
+        mut,
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    // LP token mint of the pool
+    #[account(
+        seeds = [b"lp", config.key().as_ref()],
+        bump = config.lp_bump,
+    )]
+    pub mint_lp: Account<'info, Mint>,
+
+    // Owner's LP token account - where the burned tokens come from
+    #[account(
+        mut,
+        associated_token::mint = mint_lp,
+        associated_token::authority = owner,
+    )]
+    pub owner_lp: Account<'info, TokenAccount>,
+
+    /// CHECK: The pool's lock PDA, which never signs, so it holds no data and only owns the lock token account
+    #[account(address = Config::lock_authority(&config.key()) @ AmmError::InvalidAccountList)]
+    pub lock_authority: UncheckedAccount<'info>,
+
+    // Lock PDA's LP token account - tokens sent here can't be moved again
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = mint_lp,
+        associated_token::authority = lock_authority,
+    )]
+    pub lock_lp: Account<'info, TokenAccount>,
+
+    // Required program accounts
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> BurnLock<'info> {
+    // Send `amount` LP tokens to the lock PDA for good, still counting them as pool liquidity
+    pub fn burn_lock(&mut self, amount: u64) -> Result<()> {
+        require!(amount > 0, AmmError::InvalidAmount);
+
+        self.config.burned_lp = self.config.burned_lp.checked_add(amount).ok_or(AmmError::Overflow)?;
+
+        let cpi_program = self.token_program.to_account_info();
+
+        let accounts = Transfer {
+            from: self.owner_lp.to_account_info(),
+            to: self.lock_lp.to_account_info(),
+            authority: self.owner.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(cpi_program, accounts);
+
+        transfer(cpi_ctx, amount)
+    }
+}
+
+// This struct defines the accounts needed to read how much of a pool's LP is locked
+#[derive(Accounts)]
+pub struct LockedLiquidity<'info> {
+    // Pool configuration - keeps the pool's totals of locked LP
+    #[account(
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+impl<'info> LockedLiquidity<'info> {
+    // Total LP tokens locked for this pool, in time-locks or forever
+    pub fn locked_liquidity(&self) -> Result<u64> {
+        Ok(self.config.time_locked_lp.checked_add(self.config.burned_lp).ok_or(AmmError::Overflow)?)
+    }
+}
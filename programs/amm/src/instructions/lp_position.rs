@@ -0,0 +1,88 @@
+use anchor_lang::prelude::*;
+
+use crate::{errors::AmmError, state::{Config, LpPosition, MAX_EARLY_EXIT_FEE}};
+
+// This struct defines all the accounts needed to open a position tracking a depositor's fee earnings
+// Once open, passing it to deposits and withdrawals keeps it up to date
+#[derive(Accounts)]
+pub struct OpenPosition<'info> {
+    // The depositor (pays for the position account)
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    // Pool configuration - holds the fee growth accumulators
+    #[account(
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    // The new position account
+    #[account(
+        init,
+        payer = owner,
+        seeds = [b"position", config.key().as_ref(), owner.key().as_ref()],
+        bump,
+        space = LpPosition::INIT_SPACE,
+    )]
+    pub position: Account<'info, LpPosition>,
+
+    // Required program accounts
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> OpenPosition<'info> {
+    // Open an empty position, starting from the pool's current fee growth
+    // The holding period starts now, so a fresh position can't withdraw LP moved in from elsewhere straight away
+    pub fn open_position(&mut self, bumps: OpenPositionBumps) -> Result<()> {
+        self.position.set_inner(LpPosition {
+            owner: self.owner.key(),
+            config: self.config.key(),
+            lp: 0,
+            deposited_x: 0,
+            deposited_y: 0,
+            withdrawn_x: 0,
+            withdrawn_y: 0,
+            fee_growth_x: self.config.fee_growth_x,
+            fee_growth_y: self.config.fee_growth_y,
+            fees_x: 0,
+            fees_y: 0,
+            last_deposit: Clock::get()?.unix_timestamp,
+            position_bump: bumps.position,
+        });
+
+        Ok(())
+    }
+}
+
+// This struct defines the accounts needed for the pool authority to set a minimum holding period for LP
+// Deposits made just before a large swap and withdrawn right after then pay a penalty to the other LPs
+#[derive(Accounts)]
+pub struct SetHoldPeriod<'info> {
+    // The pool authority
+    pub authority: Signer<'info>,
+
+    // Pool configuration - stores the holding period and penalty
+    #[account(
+        mut,
+        constraint = config.authority == Some(authority.key()) @ AmmError::InvalidAuthority,
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+impl<'info> SetHoldPeriod<'info> {
+    // Set the holding period in seconds (0 = off) and the penalty in basis points for withdrawing inside it
+    // While it's on, deposits and withdrawals must pass the depositor's position
+    // Positions opened to meet it start their period when they open, so turning it on never traps existing LP
+    pub fn set_hold_period(&mut self, min_hold: i64, early_exit_fee: u16) -> Result<()> {
+        require!(min_hold >= 0, AmmError::InvalidAmount);
+        require!(early_exit_fee <= MAX_EARLY_EXIT_FEE, AmmError::InvalidFee);
+
+        self.config.min_hold = min_hold;
+        self.config.early_exit_fee = early_exit_fee;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,180 @@
+use anchor_lang::{prelude::*, system_program::{transfer, Transfer}};
+use anchor_spl::{associated_token::AssociatedToken, token::{Mint, TokenAccount}};
+
+use crate::{errors::AmmError, state::{Config, LegacyConfig, LEGACY_CONFIG_SPACE}};
+
+// This struct defines all the accounts needed to bring a pool created with the original config layout up to date
+// Pools made before curves and pool state were added can't be loaded as a `Config` until this runs
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+pub struct MigrateConfig<'info> {
+    // Whoever runs the migration (pays the rent for the larger account)
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    // The two tokens that can be traded in this pool
+    pub mint_x: Account<'info, Mint>,
+    pub mint_y: Account<'info, Mint>,
+
+    /// CHECK: The pool's config in the legacy layout, which `Config` can't deserialize yet;
+    /// the seeds are checked here and the owner, length and discriminator by `read_legacy_config`
+    #[account(
+        mut,
+        seeds = [b"config", seed.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub config: UncheckedAccount<'info>,
+
+    // LP token mint - its supply becomes the pool's tracked LP supply
+    #[account(
+        seeds = [b"lp", config.key().as_ref()],
+        bump,
+    )]
+    pub mint_lp: Account<'info, Mint>,
+
+    // Pool vaults - legacy pools priced against everything in them, so it all becomes the reserves
+    #[account(
+        associated_token::mint = mint_x,
+        associated_token::authority = config,
+    )]
+    pub vault_x: Account<'info, TokenAccount>,
+    #[account(
+        associated_token::mint = mint_y,
+        associated_token::authority = config,
+    )]
+    pub vault_y: Account<'info, TokenAccount>,
+
+    // Required program accounts
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MigrateConfig<'info> {
+    // Read the legacy config, grow the account to the current size and write it back in the current layout
+    pub fn migrate_config(&mut self, seed: u64) -> Result<()> {
+        let config = self.config.to_account_info();
+
+        let legacy = read_legacy_config(&config)?;
+        require!(legacy.seed == seed, AmmError::InvalidAccountList);
+        require_keys_eq!(legacy.mint_x, self.mint_x.key(), AmmError::InvalidToken);
+        require_keys_eq!(legacy.mint_y, self.mint_y.key(), AmmError::InvalidToken);
+
+        // Start tracking the reserves and LP supply the legacy pool read straight from its accounts
+        let mut migrated = Config::from_legacy(legacy);
+        migrated.record_deposit(self.vault_x.amount, self.vault_y.amount)?;
+        migrated.record_lp_minted(self.mint_lp.supply)?;
+
+        // Top the account up so it stays rent exempt at its new size
+        let rent = Rent::get()?.minimum_balance(Config::INIT_SPACE).saturating_sub(config.lamports());
+        if rent != 0 {
+            let accounts = Transfer {
+                from: self.payer.to_account_info(),
+                to: config.clone(),
+            };
+            let cpi_ctx = CpiContext::new(self.system_program.to_account_info(), accounts);
+            transfer(cpi_ctx, rent)?;
+        }
+
+        write_migrated_config(&config, &migrated)
+    }
+}
+
+// Read a config account still in the legacy layout, checking it's this program's and the right kind of account
+pub fn read_legacy_config(config: &AccountInfo) -> Result<LegacyConfig> {
+    require_keys_eq!(*config.owner, crate::ID, ErrorCode::AccountOwnedByWrongProgram);
+    // Only accounts still in the legacy layout need migrating
+    require!(config.data_len() == LEGACY_CONFIG_SPACE, AmmError::ConfigUpToDate);
+
+    let data = config.try_borrow_data()?;
+    require!(data.starts_with(Config::DISCRIMINATOR), ErrorCode::AccountDiscriminatorMismatch);
+    Ok(LegacyConfig::deserialize(&mut &data[8..])?)
+}
+
+// Grow a legacy config account to the current size and write the migrated config into it
+pub fn write_migrated_config(config: &AccountInfo, migrated: &Config) -> Result<()> {
+    config.resize(Config::INIT_SPACE)?;
+
+    let mut data = config.try_borrow_mut_data()?;
+    migrated.try_serialize(&mut &mut data[..])
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::solana_program::entrypoint::MAX_PERMITTED_DATA_INCREASE;
+
+    use super::*;
+
+    // A config account laid out the way the runtime hands accounts to a program, which `resize` relies on:
+    // the original data length sits just before the key, the current length just before the data,
+    // and there's room after the data to grow into
+    fn legacy_account(legacy: &LegacyConfig, owner: Pubkey) -> AccountInfo<'static> {
+        let words = (48 + LEGACY_CONFIG_SPACE + MAX_PERMITTED_DATA_INCREASE).div_ceil(8);
+        let buffer: &'static mut [u64] = Box::leak(vec![0u64; words].into_boxed_slice());
+        let bytes = unsafe { std::slice::from_raw_parts_mut(buffer.as_mut_ptr() as *mut u8, words * 8) };
+
+        bytes[4..8].copy_from_slice(&(LEGACY_CONFIG_SPACE as u32).to_le_bytes());
+        bytes[8..40].copy_from_slice(Pubkey::new_unique().as_ref());
+        bytes[40..48].copy_from_slice(&(LEGACY_CONFIG_SPACE as u64).to_le_bytes());
+        bytes[48..56].copy_from_slice(Config::DISCRIMINATOR);
+        legacy.serialize(&mut &mut bytes[56..48 + LEGACY_CONFIG_SPACE]).unwrap();
+
+        let (head, data) = bytes.split_at_mut(48);
+        let key = unsafe { &*(head[8..40].as_ptr() as *const Pubkey) };
+        AccountInfo::new(
+            key,
+            false,
+            true,
+            Box::leak(Box::new(1_000_000)),
+            &mut data[..LEGACY_CONFIG_SPACE],
+            Box::leak(Box::new(owner)),
+            false,
+            0,
+        )
+    }
+
+    fn legacy() -> LegacyConfig {
+        LegacyConfig {
+            seed: 7,
+            authority: Some(Pubkey::new_unique()),
+            mint_x: Pubkey::new_unique(),
+            mint_y: Pubkey::new_unique(),
+            fee: 25,
+            locked: false,
+            config_bump: 254,
+            lp_bump: 253,
+        }
+    }
+
+    #[test]
+    fn migration_rewrites_legacy_configs_in_place() {
+        let legacy = legacy();
+        let (authority, mint_x, mint_y) = (legacy.authority, legacy.mint_x, legacy.mint_y);
+        let account = legacy_account(&legacy, crate::ID);
+
+        let read = read_legacy_config(&account).unwrap();
+        let mut migrated = Config::from_legacy(read);
+        migrated.record_deposit(5_000, 7_000).unwrap();
+        migrated.record_lp_minted(6_000).unwrap();
+        write_migrated_config(&account, &migrated).unwrap();
+
+        // The account now loads as a current config with the legacy settings and the pool's balances
+        assert_eq!(account.data_len(), Config::INIT_SPACE);
+        let config = Config::try_deserialize(&mut &account.try_borrow_data().unwrap()[..]).unwrap();
+        assert_eq!((config.seed, config.authority, config.mint_x, config.mint_y), (7, authority, mint_x, mint_y));
+        assert_eq!((config.fee, config.config_bump, config.lp_bump), (25, 254, 253));
+        assert_eq!((config.reserve_x, config.reserve_y, config.lp_supply), (5_000, 7_000, 6_000));
+
+        // A migrated account can't be migrated again
+        assert!(read_legacy_config(&account).is_err());
+    }
+
+    #[test]
+    fn migration_only_reads_this_programs_configs() {
+        assert!(read_legacy_config(&legacy_account(&legacy(), Pubkey::new_unique())).is_err());
+
+        // Another account type of the same size isn't a config
+        let account = legacy_account(&legacy(), crate::ID);
+        account.try_borrow_mut_data().unwrap()[0] ^= 1;
+        assert!(read_legacy_config(&account).is_err());
+    }
+}
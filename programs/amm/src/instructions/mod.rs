@@ -5,8 +5,56 @@ pub mod initialize;
 pub mod deposit;
 pub mod withdraw;
 pub mod swap;
+pub mod price_feed;
+pub mod initialize_multi;
+pub mod deposit_multi;
+pub mod withdraw_multi;
+pub mod swap_multi;
+pub mod pool_accounts;
+pub mod swap_route;
+pub mod swap_split;
+pub mod flash_loan;
+pub mod flash_swap;
+pub mod limit_order;
+pub mod twap_order;
+pub mod batch_auction;
+pub mod commit_reveal;
+pub mod execute_intent;
+pub mod rfq;
+pub mod deposit_single;
+pub mod withdraw_single;
+pub mod reserves;
+pub mod liquidity_lock;
+pub mod farm;
+pub mod lp_position;
+pub mod graduate;
+pub mod migrate_config;
 
 pub use initialize::*;
 pub use deposit::*;
 pub use withdraw::*;
-pub use swap::*;
\ No newline at end of file
+pub use swap::*;
+pub use price_feed::*;
+pub use initialize_multi::*;
+pub use deposit_multi::*;
+pub use withdraw_multi::*;
+pub use swap_multi::*;
+pub use pool_accounts::*;
+pub use swap_route::*;
+pub use swap_split::*;
+pub use flash_loan::*;
+pub use flash_swap::*;
+pub use limit_order::*;
+pub use twap_order::*;
+pub use batch_auction::*;
+pub use commit_reveal::*;
+pub use execute_intent::*;
+pub use rfq::*;
+pub use deposit_single::*;
+pub use withdraw_single::*;
+pub use reserves::*;
+pub use liquidity_lock::*;
+pub use farm::*;
+pub use lp_position::*;
+pub use graduate::*;
+pub use migrate_config::*;
@@ -0,0 +1,184 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, TokenAccount};
+
+use crate::{errors::AmmError, state::{Config, CurveType, PriceFeed}};
+
+// Accounts for one pool in a swap across several pools (swap_route, swap_split), passed as remaining accounts:
+// [config (writable), vault_x (writable), vault_y (writable)] for every pool, followed by
+// [mint_x, mint_y, price_feed] when the pool is oracle-anchored
+pub struct PoolAccounts<'info> {
+    pub config: Account<'info, Config>,
+    pub vault_x: Account<'info, TokenAccount>,
+    pub vault_y: Account<'info, TokenAccount>,
+    pub oracle: Option<OracleAccounts<'info>>,
+}
+
+// Extra accounts an oracle-anchored pool needs to read its price
+pub struct OracleAccounts<'info> {
+    pub mint_x: Account<'info, Mint>,
+    pub mint_y: Account<'info, Mint>,
+    pub price_feed: Account<'info, PriceFeed>,
+}
+
+impl<'info> PoolAccounts<'info> {
+    // Load the next pool from the remaining accounts, checking it can be traded directly
+    pub fn load(accounts: &mut std::slice::Iter<'info, AccountInfo<'info>>) -> Result<Self> {
+        let config: Account<'info, Config> = Account::try_from(next_account(accounts)?)?;
+
+        require!(!config.locked, AmmError::PoolLocked);
+        config.check_direct_trading()?;
+        require!(config.curve != CurveType::BondingCurve, AmmError::LaunchNotGraduated);
+
+        let vault_x = config.vault(config.key(), true, next_account(accounts)?)?;
+        let vault_y = config.vault(config.key(), false, next_account(accounts)?)?;
+
+        let oracle = match config.curve {
+            CurveType::Oracle => {
+                let mint_x: Account<'info, Mint> = Account::try_from(next_account(accounts)?)?;
+                let mint_y: Account<'info, Mint> = Account::try_from(next_account(accounts)?)?;
+                let price_feed: Account<'info, PriceFeed> = Account::try_from(next_account(accounts)?)?;
+                require_keys_eq!(mint_x.key(), config.mint_x, AmmError::InvalidToken);
+                require_keys_eq!(mint_y.key(), config.mint_y, AmmError::InvalidToken);
+                require!(config.oracle == Some(price_feed.key()), AmmError::InvalidOracle);
+
+                Some(OracleAccounts { mint_x, mint_y, price_feed })
+            }
+            _ => None,
+        };
+
+        Ok(PoolAccounts { config, vault_x, vault_y, oracle })
+    }
+
+    // Read the oracle price if the pool is anchored to one
+    pub fn price(&self) -> Result<Option<u128>> {
+        match &self.oracle {
+            Some(oracle) => self.config.oracle_price(Some(&oracle.price_feed), oracle.mint_x.decimals, oracle.mint_y.decimals),
+            None => self.config.oracle_price(None, 0, 0),
+        }
+    }
+}
+
+// Take the next remaining account, failing if the list is cut short
+pub fn next_account<'info>(accounts: &mut std::slice::Iter<'info, AccountInfo<'info>>) -> Result<&'info AccountInfo<'info>> {
+    accounts.next().ok_or(error!(AmmError::InvalidAccountList))
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::solana_program::program_pack::Pack;
+    use anchor_spl::{associated_token::get_associated_token_address, token::spl_token};
+
+    use super::*;
+    use crate::state::config::tests::config;
+
+    fn account(key: Pubkey, owner: Pubkey, data: Vec<u8>) -> AccountInfo<'static> {
+        AccountInfo::new(
+            Box::leak(Box::new(key)),
+            false,
+            true,
+            Box::leak(Box::new(1_000_000)),
+            Box::leak(data.into_boxed_slice()),
+            Box::leak(Box::new(owner)),
+            false,
+            0,
+        )
+    }
+
+    fn config_account(key: Pubkey, config: &Config) -> AccountInfo<'static> {
+        let mut data = Vec::new();
+        config.try_serialize(&mut data).unwrap();
+        account(key, crate::ID, data)
+    }
+
+    fn vault(config: Pubkey, mint: Pubkey) -> AccountInfo<'static> {
+        let mut data = vec![0; spl_token::state::Account::LEN];
+        let state = spl_token::state::Account {
+            mint,
+            owner: config,
+            state: spl_token::state::AccountState::Initialized,
+            ..Default::default()
+        };
+        spl_token::state::Account::pack(state, &mut data).unwrap();
+        account(get_associated_token_address(&config, &mint), spl_token::ID, data)
+    }
+
+    fn mint(key: Pubkey) -> AccountInfo<'static> {
+        let mut data = vec![0; spl_token::state::Mint::LEN];
+        let state = spl_token::state::Mint { decimals: 6, is_initialized: true, ..Default::default() };
+        spl_token::state::Mint::pack(state, &mut data).unwrap();
+        account(key, spl_token::ID, data)
+    }
+
+    fn price_feed(key: Pubkey) -> AccountInfo<'static> {
+        let feed = PriceFeed { authority: Pubkey::new_unique(), price: 1, conf: 0, expo: 0, publish_time: 0 };
+        let mut data = Vec::new();
+        feed.try_serialize(&mut data).unwrap();
+        account(key, crate::ID, data)
+    }
+
+    // The accounts for one pool, laid out as `PoolAccounts::load` expects
+    fn pool(config: &Config) -> Vec<AccountInfo<'static>> {
+        let key = Pubkey::new_unique();
+        let mut accounts = vec![config_account(key, config), vault(key, config.mint_x), vault(key, config.mint_y)];
+        if let Some(oracle) = config.oracle {
+            accounts.extend([mint(config.mint_x), mint(config.mint_y), price_feed(oracle)]);
+        }
+        accounts
+    }
+
+    fn oracle_config() -> Config {
+        Config { curve: CurveType::Oracle, oracle: Some(Pubkey::new_unique()), ..config() }
+    }
+
+    fn load_all(accounts: Vec<AccountInfo<'static>>) -> Result<Vec<PoolAccounts<'static>>> {
+        let accounts: &'static [AccountInfo<'static>] = Box::leak(accounts.into_boxed_slice());
+        let mut iter = accounts.iter();
+        let mut pools = Vec::new();
+        while iter.len() != 0 {
+            pools.push(PoolAccounts::load(&mut iter)?);
+        }
+        Ok(pools)
+    }
+
+    #[test]
+    fn loads_mixed_pools_in_order() {
+        let (plain, oracle) = (config(), oracle_config());
+        let accounts = [pool(&plain), pool(&oracle), pool(&plain)].concat();
+        assert_eq!(accounts.len(), 12);
+
+        let pools = load_all(accounts).unwrap();
+        assert_eq!(pools.len(), 3);
+        assert!(pools[0].oracle.is_none());
+        assert!(pools[2].oracle.is_none());
+
+        let feed = pools[1].oracle.as_ref().unwrap();
+        assert_eq!(feed.price_feed.key(), oracle.oracle.unwrap());
+        assert_eq!(feed.mint_x.key(), oracle.mint_x);
+        assert_eq!(pools[1].vault_y.mint, oracle.mint_y);
+    }
+
+    #[test]
+    fn rejects_short_or_mismatched_lists() {
+        // An oracle pool without its price feed
+        let mut accounts = pool(&oracle_config());
+        accounts.pop();
+        assert!(load_all(accounts).is_err());
+
+        // Vaults out of order
+        let mut accounts = pool(&config());
+        accounts.swap(1, 2);
+        assert!(load_all(accounts).is_err());
+
+        // A price feed the pool wasn't configured with
+        let mut accounts = pool(&oracle_config());
+        accounts[5] = price_feed(Pubkey::new_unique());
+        assert!(load_all(accounts).is_err());
+    }
+
+    #[test]
+    fn rejects_pools_that_cant_trade_directly() {
+        assert!(load_all(pool(&Config { locked: true, ..config() })).is_err());
+        assert!(load_all(pool(&Config { batch_slots: 10, ..config() })).is_err());
+        assert!(load_all(pool(&Config { curve: CurveType::BondingCurve, ..config() })).is_err());
+    }
+}
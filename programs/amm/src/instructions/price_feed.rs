@@ -0,0 +1,70 @@
+use anchor_lang::prelude::*;
+
+use crate::{errors::AmmError, state::PriceFeed};
+
+// This struct defines all the accounts needed to create a price feed
+// Oracle-anchored pools read their reference price from one of these
+#[derive(Accounts)]
+pub struct CreatePriceFeed<'info> {
+    // The publisher of the feed (pays for the account)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    // The new price feed account
+    #[account(
+        init,
+        payer = authority,
+        space = PriceFeed::INIT_SPACE,
+    )]
+    pub price_feed: Account<'info, PriceFeed>,
+
+    // Required program accounts
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CreatePriceFeed<'info> {
+    // Create the feed with no price published yet
+    pub fn create(&mut self, expo: i32) -> Result<()> {
+        self.price_feed.set_inner(PriceFeed {
+            authority: self.authority.key(),
+            price: 0,
+            conf: 0,
+            expo,
+            publish_time: 0,
+        });
+
+        Ok(())
+    }
+}
+
+// This struct defines all the accounts needed to publish a new price
+#[derive(Accounts)]
+pub struct UpdatePriceFeed<'info> {
+    // The publisher of the feed
+    pub authority: Signer<'info>,
+
+    // The price feed being updated
+    #[account(
+        mut,
+        has_one = authority @ AmmError::InvalidAuthority,
+    )]
+    pub price_feed: Account<'info, PriceFeed>,
+}
+
+impl<'info> UpdatePriceFeed<'info> {
+    // Publish a new price observed at `publish_time`
+    pub fn update(&mut self, price: u64, conf: u64, publish_time: i64) -> Result<()> {
+        require!(price != 0, AmmError::InvalidPrice);
+
+        // Prices can't be published from the future or go back in time
+        let now = Clock::get()?.unix_timestamp;
+        require!(publish_time <= now, AmmError::InvalidPrice);
+        require!(publish_time >= self.price_feed.publish_time, AmmError::StalePrice);
+
+        self.price_feed.price = price;
+        self.price_feed.conf = conf;
+        self.price_feed.publish_time = publish_time;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,170 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{transfer, Mint, Token, TokenAccount, Transfer};
+
+use crate::{errors::AmmError, state::Config};
+
+// This struct defines the accounts needed to set the pool's reserves to what its vaults hold
+// Tokens sent straight to a vault don't affect prices until a sync counts them
+#[derive(Accounts)]
+pub struct SyncReserves<'info> {
+    // The caller - must be the pool authority unless the pool allows anyone to sync
+    pub signer: Signer<'info>,
+
+    // The two tokens that can be traded in this pool
+    pub mint_x: Account<'info, Mint>,
+    pub mint_y: Account<'info, Mint>,
+
+    // Pool configuration - stores the reserves
+    #[account(
+        mut,
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    // Pool vaults - where the AMM stores the tokens
+    #[account(
+        associated_token::mint = mint_x,
+        associated_token::authority = config,
+    )]
+    pub vault_x: Account<'info, TokenAccount>,
+    #[account(
+        associated_token::mint = mint_y,
+        associated_token::authority = config,
+    )]
+    pub vault_y: Account<'info, TokenAccount>,
+}
+
+impl<'info> SyncReserves<'info> {
+    // Count everything in the vaults as pool reserves
+    pub fn sync(&mut self) -> Result<()> {
+        // A locked pool may have a flash loan out, so its vaults don't hold the real reserves
+        require!(!self.config.locked, AmmError::PoolLocked);
+        self.config.check_sync_authority(self.signer.key())?;
+
+        self.config.reserve_x = self.vault_x.amount;
+        self.config.reserve_y = self.vault_y.amount;
+
+        Ok(())
+    }
+}
+
+// This struct defines the accounts needed to send tokens the pool doesn't count as reserves to a recipient
+#[derive(Accounts)]
+pub struct SkimReserves<'info> {
+    // The pool authority
+    pub signer: Signer<'info>,
+
+    // The two tokens that can be traded in this pool
+    pub mint_x: Account<'info, Mint>,
+    pub mint_y: Account<'info, Mint>,
+
+    // Pool configuration - stores the reserves and owns the vaults
+    #[account(
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    // Pool vaults - where the AMM stores the tokens
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = config,
+    )]
+    pub vault_x: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = config,
+    )]
+    pub vault_y: Account<'info, TokenAccount>,
+
+    // Recipient's token accounts - where the excess goes
+    #[account(
+        mut,
+        token::mint = mint_x,
+    )]
+    pub recipient_x: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = mint_y,
+    )]
+    pub recipient_y: Account<'info, TokenAccount>,
+
+    // Required program accounts
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> SkimReserves<'info> {
+    // Send whatever the vaults hold beyond the pool's reserves to the recipient
+    pub fn skim(&mut self) -> Result<()> {
+        // A locked pool may have a flash loan out, so its vaults don't hold the real reserves
+        require!(!self.config.locked, AmmError::PoolLocked);
+        self.config.check_skim_authority(self.signer.key())?;
+
+        let excess_x = self.vault_x.amount.saturating_sub(self.config.reserve_x);
+        let excess_y = self.vault_y.amount.saturating_sub(self.config.reserve_y);
+
+        if excess_x != 0 {
+            self.withdraw_tokens(self.vault_x.to_account_info(), self.recipient_x.to_account_info(), excess_x)?;
+        }
+        if excess_y != 0 {
+            self.withdraw_tokens(self.vault_y.to_account_info(), self.recipient_y.to_account_info(), excess_y)?;
+        }
+
+        Ok(())
+    }
+
+    // Helper function to transfer tokens from a pool vault
+    pub fn withdraw_tokens(&self, from: AccountInfo<'info>, to: AccountInfo<'info>, amount: u64) -> Result<()> {
+        let cpi_program = self.token_program.to_account_info();
+
+        let accounts = Transfer {
+            from,
+            to,
+            authority: self.config.to_account_info(),
+        };
+
+        // Create the authority seeds for the config account
+        let seeds = &[
+            &b"config"[..],
+            &self.config.seed.to_le_bytes(),
+            &[self.config.config_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, accounts, signer_seeds);
+
+        transfer(cpi_ctx, amount)
+    }
+}
+
+// This struct defines the accounts needed for the pool authority to choose who can sync
+#[derive(Accounts)]
+pub struct SetSyncMode<'info> {
+    // The pool authority
+    pub authority: Signer<'info>,
+
+    // Pool configuration - stores the sync mode
+    #[account(
+        mut,
+        constraint = config.authority == Some(authority.key()) @ AmmError::InvalidAuthority,
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+impl<'info> SetSyncMode<'info> {
+    // Allow anyone to sync, or only the authority (skimming is always left to the authority)
+    pub fn set_sync_mode(&mut self, permissionless: bool) -> Result<()> {
+        self.config.permissionless_sync = permissionless;
+
+        Ok(())
+    }
+}
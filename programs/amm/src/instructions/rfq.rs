@@ -0,0 +1,254 @@
+use anchor_lang::{prelude::*, solana_program::sysvar::instructions};
+use anchor_spl::{associated_token::AssociatedToken, token::{transfer, Mint, Token, TokenAccount, Transfer}};
+
+use crate::{errors::AmmError, math, signature, state::{Config, CurveType, PriceFeed, QuoteFill, MAX_MARKET_MAKERS}};
+
+// This struct defines all the accounts needed to fill a swap from a market maker's signed quote
+// The market maker approves the pool's config as delegate on its output token account and signs
+// the quote off-chain; the user submits it with an ed25519 program instruction right before this one
+#[derive(Accounts)]
+#[instruction(quote: RfqQuote)]
+pub struct FillRfq<'info> {
+    // The user who wants to swap tokens (pays for the transaction)
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // The market maker who signed the quote
+    #[account(address = quote.market_maker @ AmmError::InvalidSignature)]
+    pub market_maker: SystemAccount<'info>,
+
+    // The two tokens that can be traded in this pool
+    pub mint_x: Account<'info, Mint>,
+    pub mint_y: Account<'info, Mint>,
+
+    // User's token accounts - where their tokens are stored
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint_x,
+        associated_token::authority = user,
+    )]
+    pub user_x: Account<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint_y,
+        associated_token::authority = user,
+    )]
+    pub user_y: Account<'info, TokenAccount>,
+
+    // Market maker's token accounts - it receives the input in one and pays the output from the other
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = market_maker,
+    )]
+    pub market_maker_x: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = market_maker,
+    )]
+    pub market_maker_y: Account<'info, TokenAccount>,
+
+    // Pool vaults - where the AMM stores the tokens
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = config,
+    )]
+    pub vault_x: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = config,
+    )]
+    pub vault_y: Account<'info, TokenAccount>,
+
+    // Pool configuration - holds the market maker allowlist and is the delegate on the market maker's account
+    #[account(
+        mut,
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    // Price feed - only needed for oracle-anchored pools
+    pub price_feed: Option<Account<'info, PriceFeed>>,
+
+    // Record of how much of the quote has been filled, created by its first fill
+    #[account(
+        init_if_needed,
+        payer = user,
+        seeds = [b"quote", quote.market_maker.as_ref(), quote.id.to_le_bytes().as_ref()],
+        bump,
+        space = QuoteFill::INIT_SPACE,
+    )]
+    pub quote_fill: Account<'info, QuoteFill>,
+
+    /// CHECK: The instructions sysvar, used to find the ed25519 signature check
+    #[account(address = instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    // Required program accounts
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+// A market maker's firm quote; the borsh-serialized struct is the signed message
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct RfqQuote {
+    pub pool: Pubkey, // Config of the pool the quote is offered through
+    pub market_maker: Pubkey, // Market maker signing the quote
+    pub taker: Pubkey, // User the quote is for
+    pub is_x: bool, // Whether the taker sells token X
+    pub amount_in: u64, // Most input the market maker takes at this price, across all fills
+    pub amount_out: u64, // Output paid for the full amount_in (smaller fills are pro-rata)
+    pub id: u64, // Unique per market maker, so the quote fills only once
+    pub expiry: i64, // Unix timestamp after which the quote can't fill
+}
+
+impl<'info> FillRfq<'info> {
+    // Fill as much of `amount` as the quote covers from the market maker, and the rest from the curve,
+    // with one slippage check over the total
+    pub fn fill(&mut self, quote: RfqQuote, amount: u64, min: u64) -> Result<()> {
+        // Check that the pool is not locked
+        require!(!self.config.locked, AmmError::PoolLocked);
+        require!(amount > 0, AmmError::InvalidAmount);
+
+        // The quote must be from an allowed market maker, for this pool, trade and user, and still live
+        require!(self.config.market_makers.contains(&quote.market_maker), AmmError::NotMarketMaker);
+        require_keys_eq!(quote.pool, self.config.key(), AmmError::InvalidSignature);
+        require_keys_eq!(quote.taker, self.user.key(), AmmError::InvalidSignature);
+        require!(quote.amount_in > 0, AmmError::InvalidAmount);
+        require!(Clock::get()?.unix_timestamp <= quote.expiry, AmmError::QuoteExpired);
+
+        // The market maker must have signed exactly this quote
+        let mut message = Vec::new();
+        quote.serialize(&mut message)?;
+        signature::verify_previous_ed25519(&self.instructions.to_account_info(), &quote.market_maker, &message)?;
+
+        if self.quote_fill.market_maker == Pubkey::default() {
+            self.quote_fill.set_inner(QuoteFill {
+                market_maker: quote.market_maker,
+                id: quote.id,
+                filled: 0,
+            });
+        }
+
+        // Fill from the market maker first, at the quoted price, up to what's left of the quote
+        let is_x = quote.is_x;
+        let rfq_in = self.quote_fill.fill(quote.amount_in, amount)?;
+        let rfq_out = math::mul_div_floor(quote.amount_out, rfq_in, quote.amount_in)?;
+
+        let (user_in, user_out, market_maker_in, market_maker_out) = match is_x {
+            true => (&self.user_x, &self.user_y, &self.market_maker_x, &self.market_maker_y),
+            false => (&self.user_y, &self.user_x, &self.market_maker_y, &self.market_maker_x),
+        };
+        self.user_transfer(user_in.to_account_info(), market_maker_in.to_account_info(), rfq_in)?;
+        if rfq_out != 0 {
+            self.config_transfer(market_maker_out.to_account_info(), user_out.to_account_info(), rfq_out)?;
+        }
+
+        // Anything the quote doesn't cover goes through the pool's curve like a regular swap
+        let curve_in = amount - rfq_in;
+        let mut curve_out = 0;
+        if curve_in != 0 {
+            self.config.check_direct_trading()?;
+            require!(self.config.curve != CurveType::BondingCurve, AmmError::LaunchNotGraduated);
+
+            // Read the oracle price if the pool is anchored to one
+            let price = self.config.oracle_price(
+                self.price_feed.as_ref(),
+                self.mint_x.decimals,
+                self.mint_y.decimals,
+            )?;
+
+            let res = self.config.pool_swap(is_x, curve_in, price)?;
+            require!(res.withdraw != 0, AmmError::InvalidAmount);
+
+            let (vault_in, vault_out) = match is_x {
+                true => (&self.vault_x, &self.vault_y),
+                false => (&self.vault_y, &self.vault_x),
+            };
+            self.user_transfer(user_in.to_account_info(), vault_in.to_account_info(), res.deposit)?;
+            self.config_transfer(vault_out.to_account_info(), user_out.to_account_info(), res.withdraw)?;
+            curve_out = res.withdraw;
+        }
+
+        // One slippage check for the whole fill
+        let total_out = rfq_out.checked_add(curve_out).ok_or(AmmError::Overflow)?;
+        require!(total_out >= min, AmmError::SlippageExceeded);
+
+        Ok(())
+    }
+
+    // Helper function to transfer tokens from the user
+    pub fn user_transfer(&self, from: AccountInfo<'info>, to: AccountInfo<'info>, amount: u64) -> Result<()> {
+        let cpi_program = self.token_program.to_account_info();
+
+        let accounts = Transfer {
+            from,
+            to,
+            authority: self.user.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(cpi_program, accounts);
+
+        transfer(cpi_ctx, amount)
+    }
+
+    // Helper function to transfer tokens with the config as authority
+    // It owns the vaults and is the delegate on the market maker's output account
+    pub fn config_transfer(&self, from: AccountInfo<'info>, to: AccountInfo<'info>, amount: u64) -> Result<()> {
+        let cpi_program = self.token_program.to_account_info();
+
+        let accounts = Transfer {
+            from,
+            to,
+            authority: self.config.to_account_info(),
+        };
+
+        // Create the authority seeds for the config account
+        let seeds = &[
+            &b"config"[..],
+            &self.config.seed.to_le_bytes(),
+            &[self.config.config_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, accounts, signer_seeds);
+
+        transfer(cpi_ctx, amount)
+    }
+}
+
+// This struct defines the accounts needed for the pool authority to change the market maker allowlist
+#[derive(Accounts)]
+pub struct SetMarketMakers<'info> {
+    // The pool authority
+    pub authority: Signer<'info>,
+
+    // Pool configuration - stores the allowlist
+    #[account(
+        mut,
+        constraint = config.authority == Some(authority.key()) @ AmmError::InvalidAuthority,
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+impl<'info> SetMarketMakers<'info> {
+    // Replace the allowlist of market makers whose quotes can fill swaps
+    pub fn set_market_makers(&mut self, market_makers: Vec<Pubkey>) -> Result<()> {
+        require!(market_makers.len() <= MAX_MARKET_MAKERS, AmmError::InvalidAmount);
+
+        self.config.market_makers = market_makers;
+
+        Ok(())
+    }
+}
@@ -1,11 +1,9 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{associated_token::AssociatedToken, token::{Mint, Token, TokenAccount, Transfer, transfer}};
-use constant_product_curve::{ConstantProduct, LiquidityPair};
-
-use crate::{errors::AmmError, state::Config};
+use crate::{errors::AmmError, state::{Config, PriceFeed}};
 
 // This struct defines all the accounts needed to swap tokens in the AMM
-// Users can trade one token for another using the constant product formula
+// Users can trade one token for another using the pool's pricing curve
 #[derive(Accounts)]
 pub struct Swap<'info> {
     // The user who wants to swap tokens (pays for the transaction)
@@ -48,13 +46,17 @@ pub struct Swap<'info> {
     
     // Pool configuration - contains all the pool settings
     #[account(
+        mut,
         has_one = mint_x,
         has_one = mint_y,
         seeds = [b"config", config.seed.to_le_bytes().as_ref()],
         bump = config.config_bump,
     )]
     pub config: Account<'info, Config>,
-    
+
+    // Price feed - only needed for oracle-anchored pools
+    pub price_feed: Option<Account<'info, PriceFeed>>,
+
     // Required program accounts
     pub token_program: Program<'info, Token>,
     pub associated_token_program: Program<'info, AssociatedToken>,
@@ -65,27 +67,25 @@ impl<'info> Swap<'info> {
     // Main swap function - trades one token for another
     pub fn swap(&mut self, is_x: bool, amount: u64, min: u64) -> Result<()> {
         // Check that the pool is not locked
-        require!(self.config.locked == false, AmmError::PoolLocked);
+        require!(!self.config.locked, AmmError::PoolLocked);
+        self.config.check_direct_trading()?;
         require!(amount > 0, AmmError::InvalidAmount);
 
-        // Create the constant product curve with current pool state
-        let mut curve = ConstantProduct::init(
-            self.vault_x.amount,
-             self.vault_y.amount, 
-             self.vault_x.amount, 
-             self.config.fee,
-            None,
-        )
-        .map_err(AmmError::from)?;
-
-        // Determine which token is being swapped
-        let p = match is_x {
-            true => LiquidityPair::X,
-            false => LiquidityPair::Y,
-        };
+        // A launch that hit its raise target stops trading until it graduates
+        require!(!self.config.should_graduate(self.config.reserve_y), AmmError::GraduationPending);
+
+        // Read the oracle price if the pool is anchored to one
+        let price = self.config.oracle_price(
+            self.price_feed.as_ref(),
+            self.mint_x.decimals,
+            self.mint_y.decimals,
+        )?;
 
-        // Calculate the swap using the constant product formula
-        let res = curve.swap(p, amount, min).map_err(AmmError::from)?;
+        // Calculate the swap using the pool's pricing curve
+        let res = self.config.pool_swap(is_x, amount, price)?;
+
+        // Check that the output meets the user's minimum
+        require!(res.withdraw >= min, AmmError::SlippageExceeded);
 
         // Verify the swap amounts are valid
         require!(res.deposit != 0, AmmError::InvalidAmount);
@@ -151,4 +151,4 @@ impl<'info> Swap<'info> {
 
         Ok(())
     }
-}
\ No newline at end of file
+}
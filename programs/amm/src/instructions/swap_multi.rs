@@ -0,0 +1,105 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{transfer, Token, Transfer};
+
+use crate::{errors::AmmError, math, state::MultiPool};
+
+// This struct defines all the accounts needed to swap inside a multi-asset pool
+// The accounts for the traded pair are passed as remaining accounts:
+// [vault_in, user_in, vault_out, user_out]
+#[derive(Accounts)]
+pub struct SwapMulti<'info> {
+    // The user who wants to swap tokens
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // Pool account - contains the list of mints, reserves and pool settings
+    #[account(
+        mut,
+        seeds = [b"multi", pool.seed.to_le_bytes().as_ref()],
+        bump = pool.pool_bump,
+    )]
+    pub pool: Account<'info, MultiPool>,
+
+    // Required program accounts
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> SwapMulti<'info> {
+    // Main swap function - trades token `index_in` for token `index_out`
+    // The pool keeps the product of all reserves constant (equal weights), so
+    // a swap between two tokens is a constant product swap on that pair
+    pub fn swap(
+        &mut self,
+        index_in: u8,
+        index_out: u8,
+        amount: u64,
+        min: u64,
+        remaining_accounts: &'info [AccountInfo<'info>],
+    ) -> Result<()> {
+        // Check that the pool is not locked
+        require!(!self.pool.locked, AmmError::PoolLocked);
+        require!(amount > 0, AmmError::InvalidAmount);
+        require!(index_in != index_out, AmmError::InvalidToken);
+        require!(remaining_accounts.len() == 4, AmmError::InvalidAccountList);
+
+        let pool_key = self.pool.key();
+        let vault_in = self.pool.vault(pool_key, index_in as usize, &remaining_accounts[0])?;
+        let user_in = self.pool.token_account(index_in as usize, &remaining_accounts[1])?;
+        let vault_out = self.pool.vault(pool_key, index_out as usize, &remaining_accounts[2])?;
+        let user_out = self.pool.token_account(index_out as usize, &remaining_accounts[3])?;
+
+        // Calculate the swap using the constant product formula on the pair's reserves
+        let (index_in, index_out) = (index_in as usize, index_out as usize);
+        let res = math::swap_amount_out(self.pool.reserves[index_in], self.pool.reserves[index_out], amount, self.pool.fee)?;
+
+        // Check that the output meets the user's minimum
+        require!(res.withdraw >= min, AmmError::SlippageExceeded);
+        require!(res.withdraw != 0, AmmError::InvalidAmount);
+
+        // Execute the swap by transferring tokens
+        self.pool.record_deposit(index_in, res.deposit)?;
+        self.pool.record_withdraw(index_out, res.withdraw)?;
+        self.deposit_tokens(user_in.to_account_info(), vault_in.to_account_info(), res.deposit)?;
+        self.withdraw_tokens(vault_out.to_account_info(), user_out.to_account_info(), res.withdraw)?;
+
+        Ok(())
+    }
+
+    // Helper function to transfer tokens from user to pool vault
+    pub fn deposit_tokens(&self, from: AccountInfo<'info>, to: AccountInfo<'info>, amount: u64) -> Result<()> {
+        let cpi_program = self.token_program.to_account_info();
+
+        let accounts = Transfer {
+            from,
+            to,
+            authority: self.user.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(cpi_program, accounts);
+
+        transfer(cpi_ctx, amount)
+    }
+
+    // Helper function to transfer tokens from pool vault to user
+    pub fn withdraw_tokens(&self, from: AccountInfo<'info>, to: AccountInfo<'info>, amount: u64) -> Result<()> {
+        let cpi_program = self.token_program.to_account_info();
+
+        let accounts = Transfer {
+            from,
+            to,
+            authority: self.pool.to_account_info(),
+        };
+
+        // Create the authority seeds for the pool account
+        let seeds = &[
+            &b"multi"[..],
+            &self.pool.seed.to_le_bytes(),
+            &[self.pool.pool_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, accounts, signer_seeds);
+
+        transfer(cpi_ctx, amount)
+    }
+}
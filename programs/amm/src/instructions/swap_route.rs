@@ -0,0 +1,142 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{transfer, Token, TokenAccount, Transfer};
+
+use crate::{errors::AmmError, state::Config};
+
+use super::PoolAccounts;
+
+// This struct defines all the accounts needed to swap through several pools in a row
+// Each hop's pool is passed as remaining accounts in route order, laid out as in `PoolAccounts`
+// Intermediate tokens move straight from one pool's vault to the next, so the user
+// never needs token accounts for them
+#[derive(Accounts)]
+pub struct SwapRoute<'info> {
+    // The user who wants to swap tokens
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // User's token account for the first input token
+    #[account(
+        mut,
+        token::authority = user,
+    )]
+    pub user_in: Account<'info, TokenAccount>,
+
+    // User's token account for the final output token
+    #[account(
+        mut,
+        token::authority = user,
+    )]
+    pub user_out: Account<'info, TokenAccount>,
+
+    // Required program accounts
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> SwapRoute<'info> {
+    // Main route function - swaps `amount` of the first token through every pool in order
+    pub fn swap_route(
+        &mut self,
+        amount: u64,
+        min: u64,
+        remaining_accounts: &'info [AccountInfo<'info>],
+    ) -> Result<()> {
+        require!(amount > 0, AmmError::InvalidAmount);
+
+        let mut accounts = remaining_accounts.iter();
+        let mut mint_in = self.user_in.mint;
+        let mut amount_in = amount;
+
+        // Output of the previous hop, still sitting in that pool's vault
+        let mut previous: Option<(Account<'info, Config>, Account<'info, TokenAccount>)> = None;
+        let mut visited: Vec<Pubkey> = Vec::new();
+
+        while accounts.len() != 0 {
+            let pool = PoolAccounts::load(&mut accounts)?;
+            let price = pool.price()?;
+            let PoolAccounts { mut config, vault_x, vault_y, .. } = pool;
+
+            // Check that the pool only appears once in the route
+            require!(!visited.contains(&config.key()), AmmError::InvalidAccountList);
+            visited.push(config.key());
+
+            // Determine which token is being swapped from the token coming in
+            let is_x = match mint_in {
+                mint if mint == config.mint_x => true,
+                mint if mint == config.mint_y => false,
+                _ => return err!(AmmError::InvalidToken),
+            };
+
+            // Calculate the hop using the pool's pricing curve, exactly like a single swap
+            let res = config.pool_swap(is_x, amount_in, price)?;
+            require!(res.deposit != 0, AmmError::InvalidAmount);
+            require!(res.withdraw != 0, AmmError::InvalidAmount);
+
+            let (vault_in, vault_out) = match is_x {
+                true => (vault_x, vault_y),
+                false => (vault_y, vault_x),
+            };
+
+            // Pay this hop from the user on the first hop, or straight from the previous pool
+            match previous.take() {
+                None => self.deposit_tokens(vault_in.to_account_info(), res.deposit)?,
+                Some((previous_config, previous_vault)) => {
+                    self.withdraw_tokens(&previous_config, previous_vault.to_account_info(), vault_in.to_account_info(), res.deposit)?
+                }
+            }
+
+            // Persist the new reserves and any curve state the swap updated (e.g. PMM targets)
+            config.exit(&crate::ID)?;
+
+            mint_in = vault_out.mint;
+            amount_in = res.withdraw;
+            previous = Some((config, vault_out));
+        }
+
+        let (last_config, last_vault) = previous.ok_or(AmmError::InvalidAccountList)?;
+
+        // One slippage check for the whole route
+        require_keys_eq!(self.user_out.mint, mint_in, AmmError::InvalidToken);
+        require!(amount_in >= min, AmmError::SlippageExceeded);
+
+        self.withdraw_tokens(&last_config, last_vault.to_account_info(), self.user_out.to_account_info(), amount_in)
+    }
+
+    // Helper function to transfer tokens from the user to the first pool's vault
+    pub fn deposit_tokens(&self, to: AccountInfo<'info>, amount: u64) -> Result<()> {
+        let cpi_program = self.token_program.to_account_info();
+
+        let accounts = Transfer {
+            from: self.user_in.to_account_info(),
+            to,
+            authority: self.user.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(cpi_program, accounts);
+
+        transfer(cpi_ctx, amount)
+    }
+
+    // Helper function to transfer tokens out of a pool's vault
+    pub fn withdraw_tokens(&self, config: &Account<'info, Config>, from: AccountInfo<'info>, to: AccountInfo<'info>, amount: u64) -> Result<()> {
+        let cpi_program = self.token_program.to_account_info();
+
+        let accounts = Transfer {
+            from,
+            to,
+            authority: config.to_account_info(),
+        };
+
+        // Create the authority seeds for the config account
+        let seeds = &[
+            &b"config"[..],
+            &config.seed.to_le_bytes(),
+            &[config.config_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, accounts, signer_seeds);
+
+        transfer(cpi_ctx, amount)
+    }
+}
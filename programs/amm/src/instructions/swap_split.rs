@@ -0,0 +1,178 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{associated_token::AssociatedToken, token::{transfer, Mint, Token, TokenAccount, Transfer}};
+
+use crate::{errors::AmmError, math, state::Config};
+
+use super::PoolAccounts;
+
+// This struct defines all the accounts needed to split one swap across several pools
+// Every pool must trade the same pair, and is passed as remaining accounts laid out as in `PoolAccounts`
+#[derive(Accounts)]
+pub struct SwapSplit<'info> {
+    // The user who wants to swap tokens
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // The two tokens traded by every pool in the split
+    pub mint_x: Account<'info, Mint>,
+    pub mint_y: Account<'info, Mint>,
+
+    // User's token accounts - where their tokens are stored
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = user,
+    )]
+    pub user_x: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = user,
+    )]
+    pub user_y: Account<'info, TokenAccount>,
+
+    // Required program accounts
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+// One pool taking part in a split swap, with its oracle price if it has one
+pub struct SplitPool<'info> {
+    pub accounts: PoolAccounts<'info>,
+    pub price: Option<u128>,
+}
+
+impl<'info> SwapSplit<'info> {
+    // Main split function - swaps `amount` across every pool with one slippage check
+    // If `amounts` is empty the split is computed on-chain so marginal prices end up equal,
+    // otherwise it gives the amount to send to each pool
+    pub fn swap_split(
+        &mut self,
+        is_x: bool,
+        amount: u64,
+        min: u64,
+        amounts: Vec<u64>,
+        remaining_accounts: &'info [AccountInfo<'info>],
+    ) -> Result<()> {
+        require!(amount > 0, AmmError::InvalidAmount);
+
+        let mut pools = self.load_pools(remaining_accounts)?;
+
+        let amounts = match amounts.is_empty() {
+            true => math::split::split_amount(amount, pools.len(), |i, amount_in| {
+                // Quote on a copy so curve state isn't touched until the real swap
+                let pool = &pools[i];
+                let mut config = Config::clone(&pool.accounts.config);
+                let res = config.pool_swap(is_x, amount_in, pool.price)?;
+                Ok(res.withdraw)
+            })?,
+            false => {
+                require!(amounts.len() == pools.len(), AmmError::InvalidAccountList);
+                let total = amounts.iter().try_fold(0u64, |total, a| total.checked_add(*a)).ok_or(AmmError::Overflow)?;
+                require!(total == amount, AmmError::InvalidAmount);
+                amounts
+            }
+        };
+
+        let mut total_out = 0u64;
+
+        for (pool, amount_in) in pools.iter_mut().zip(amounts) {
+            if amount_in == 0 {
+                continue;
+            }
+
+            // Calculate this part using the pool's pricing curve, exactly like a single swap
+            let PoolAccounts { config, vault_x, vault_y, .. } = &mut pool.accounts;
+            let res = config.pool_swap(is_x, amount_in, pool.price)?;
+            require!(res.withdraw != 0, AmmError::InvalidAmount);
+
+            let (vault_in, vault_out) = match is_x {
+                true => (&*vault_x, &*vault_y),
+                false => (&*vault_y, &*vault_x),
+            };
+            self.deposit_tokens(is_x, vault_in.to_account_info(), res.deposit)?;
+            self.withdraw_tokens(is_x, config, vault_out.to_account_info(), res.withdraw)?;
+
+            // Persist the new reserves and any curve state the swap updated (e.g. PMM targets)
+            config.exit(&crate::ID)?;
+
+            total_out = total_out.checked_add(res.withdraw).ok_or(AmmError::Overflow)?;
+        }
+
+        // One slippage check for the whole order
+        require!(total_out >= min, AmmError::SlippageExceeded);
+
+        Ok(())
+    }
+
+    // Load and check every pool in the split
+    pub fn load_pools(&self, remaining_accounts: &'info [AccountInfo<'info>]) -> Result<Vec<SplitPool<'info>>> {
+        let mut accounts = remaining_accounts.iter();
+        let mut pools: Vec<SplitPool<'info>> = Vec::new();
+
+        while accounts.len() != 0 {
+            let pool = PoolAccounts::load(&mut accounts)?;
+            let config = &pool.config;
+
+            // Every pool must trade this pair and only appear once
+            require_keys_eq!(config.mint_x, self.mint_x.key(), AmmError::InvalidToken);
+            require_keys_eq!(config.mint_y, self.mint_y.key(), AmmError::InvalidToken);
+            require!(pools.iter().all(|other| other.accounts.config.key() != config.key()), AmmError::InvalidAccountList);
+
+            let price = pool.price()?;
+            pools.push(SplitPool { accounts: pool, price });
+        }
+
+        require!(!pools.is_empty(), AmmError::InvalidAccountList);
+
+        Ok(pools)
+    }
+
+    // Helper function to transfer tokens from user to a pool vault
+    pub fn deposit_tokens(&self, is_x: bool, to: AccountInfo<'info>, amount: u64) -> Result<()> {
+        let from = match is_x {
+            true => self.user_x.to_account_info(),
+            false => self.user_y.to_account_info(),
+        };
+
+        let cpi_program = self.token_program.to_account_info();
+
+        let accounts = Transfer {
+            from,
+            to,
+            authority: self.user.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(cpi_program, accounts);
+
+        transfer(cpi_ctx, amount)
+    }
+
+    // Helper function to transfer tokens from a pool vault to the user
+    pub fn withdraw_tokens(&self, is_x: bool, config: &Account<'info, Config>, from: AccountInfo<'info>, amount: u64) -> Result<()> {
+        let to = match is_x {
+            true => self.user_y.to_account_info(),
+            false => self.user_x.to_account_info(),
+        };
+
+        let cpi_program = self.token_program.to_account_info();
+
+        let accounts = Transfer {
+            from,
+            to,
+            authority: config.to_account_info(),
+        };
+
+        // Create the authority seeds for the config account
+        let seeds = &[
+            &b"config"[..],
+            &config.seed.to_le_bytes(),
+            &[config.config_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, accounts, signer_seeds);
+
+        transfer(cpi_ctx, amount)
+    }
+}
@@ -0,0 +1,377 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{associated_token::AssociatedToken, token::{close_account, transfer, CloseAccount, Mint, Token, TokenAccount, Transfer}};
+
+use crate::{errors::AmmError, state::{Config, CurveType, PriceFeed, TwapOrder}};
+
+// This struct defines all the accounts needed to place a TWAP order
+// The whole input moves into an escrow owned by the order and is sold slice by slice
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+pub struct PlaceTwap<'info> {
+    // The trader placing the order (pays for the accounts)
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    // The token being sold and the token being bought
+    pub mint_in: Account<'info, Mint>,
+    pub mint_out: Account<'info, Mint>,
+
+    // Pool the order trades against - it must trade this pair
+    #[account(
+        constraint = (config.mint_x == mint_in.key() && config.mint_y == mint_out.key())
+            || (config.mint_x == mint_out.key() && config.mint_y == mint_in.key()) @ AmmError::InvalidToken,
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    // Owner's token accounts - input is taken from one, and each slice's output is sent to the other
+    #[account(
+        mut,
+        associated_token::mint = mint_in,
+        associated_token::authority = owner,
+    )]
+    pub owner_in: Account<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = mint_out,
+        associated_token::authority = owner,
+    )]
+    pub owner_out: Account<'info, TokenAccount>,
+
+    // The new TWAP order account
+    #[account(
+        init,
+        payer = owner,
+        seeds = [b"twap", config.key().as_ref(), owner.key().as_ref(), seed.to_le_bytes().as_ref()],
+        bump,
+        space = TwapOrder::INIT_SPACE,
+    )]
+    pub twap: Account<'info, TwapOrder>,
+
+    // Escrow holding the input still to be sold
+    #[account(
+        init,
+        payer = owner,
+        associated_token::mint = mint_in,
+        associated_token::authority = twap,
+    )]
+    pub escrow: Account<'info, TokenAccount>,
+
+    // Required program accounts
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> PlaceTwap<'info> {
+    // Escrow `slices` slices of `slice_amount`, the first of which can execute right away
+    pub fn place(&mut self, seed: u64, slice_amount: u64, slices: u16, interval: i64, min_slice_out: u64, bumps: PlaceTwapBumps) -> Result<()> {
+        require!(slice_amount > 0, AmmError::InvalidAmount);
+        require!(slices > 0, AmmError::InvalidAmount);
+        require!(interval > 0, AmmError::InvalidAmount);
+        require!(min_slice_out > 0, AmmError::InvalidAmount);
+
+        // Launches only trade through `swap`, which stops them at the raise target until they graduate
+        require!(self.config.curve != CurveType::BondingCurve, AmmError::LaunchNotGraduated);
+
+        let amount = slice_amount.checked_mul(slices as u64).ok_or(AmmError::Overflow)?;
+
+        self.twap.set_inner(TwapOrder {
+            owner: self.owner.key(),
+            config: self.config.key(),
+            mint_in: self.mint_in.key(),
+            seed,
+            is_x: self.mint_in.key() == self.config.mint_x,
+            slice_amount,
+            slices_left: slices,
+            interval,
+            next_time: Clock::get()?.unix_timestamp,
+            min_slice_out,
+            twap_bump: bumps.twap,
+        });
+
+        self.deposit_tokens(amount)
+    }
+
+    // Helper function to transfer the input from owner to escrow
+    pub fn deposit_tokens(&self, amount: u64) -> Result<()> {
+        let cpi_program = self.token_program.to_account_info();
+
+        let accounts = Transfer {
+            from: self.owner_in.to_account_info(),
+            to: self.escrow.to_account_info(),
+            authority: self.owner.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(cpi_program, accounts);
+
+        transfer(cpi_ctx, amount)
+    }
+}
+
+// This struct defines all the accounts needed to cancel a TWAP order
+#[derive(Accounts)]
+pub struct CancelTwap<'info> {
+    // The trader who placed the order (receives the rent back)
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    // The token the order was selling
+    pub mint_in: Account<'info, Mint>,
+
+    // Owner's token account for the input - the unsold escrow is refunded here
+    #[account(
+        mut,
+        associated_token::mint = mint_in,
+        associated_token::authority = owner,
+    )]
+    pub owner_in: Account<'info, TokenAccount>,
+
+    // The TWAP order being cancelled
+    #[account(
+        mut,
+        close = owner,
+        has_one = owner,
+        has_one = mint_in,
+        seeds = [b"twap", twap.config.as_ref(), owner.key().as_ref(), twap.seed.to_le_bytes().as_ref()],
+        bump = twap.twap_bump,
+    )]
+    pub twap: Account<'info, TwapOrder>,
+
+    // Escrow holding the unsold input
+    #[account(
+        mut,
+        associated_token::mint = mint_in,
+        associated_token::authority = twap,
+    )]
+    pub escrow: Account<'info, TokenAccount>,
+
+    // Required program accounts
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> CancelTwap<'info> {
+    // Refund whatever hasn't been sold and close the escrow, the order account is closed by Anchor
+    pub fn cancel(&mut self) -> Result<()> {
+        let twap = &self.twap;
+
+        // Create the authority seeds for the TWAP order account
+        let seed = twap.seed.to_le_bytes();
+        let seeds = &[
+            &b"twap"[..],
+            twap.config.as_ref(),
+            twap.owner.as_ref(),
+            &seed,
+            &[twap.twap_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        let accounts = Transfer {
+            from: self.escrow.to_account_info(),
+            to: self.owner_in.to_account_info(),
+            authority: twap.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), accounts, signer_seeds);
+        transfer(cpi_ctx, self.escrow.amount)?;
+
+        let accounts = CloseAccount {
+            account: self.escrow.to_account_info(),
+            destination: self.owner.to_account_info(),
+            authority: twap.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), accounts, signer_seeds);
+
+        close_account(cpi_ctx)
+    }
+}
+
+// This struct defines all the accounts needed to execute the next slice of a TWAP order
+// Anyone can act as the keeper once the slice is due
+#[derive(Accounts)]
+pub struct ExecuteTwapSlice<'info> {
+    // Whoever executes the slice
+    pub keeper: Signer<'info>,
+
+    // The trader who placed the order - receives the rent back after the last slice
+    #[account(mut)]
+    pub owner: SystemAccount<'info>,
+
+    // The token being sold and the token being bought
+    pub mint_in: Account<'info, Mint>,
+    pub mint_out: Account<'info, Mint>,
+
+    // Owner's token account for the output - each slice's output is sent here
+    #[account(
+        mut,
+        associated_token::mint = mint_out,
+        associated_token::authority = owner,
+    )]
+    pub owner_out: Account<'info, TokenAccount>,
+
+    // The TWAP order being executed
+    #[account(
+        mut,
+        has_one = owner,
+        has_one = mint_in,
+        has_one = config,
+        seeds = [b"twap", config.key().as_ref(), owner.key().as_ref(), twap.seed.to_le_bytes().as_ref()],
+        bump = twap.twap_bump,
+    )]
+    pub twap: Account<'info, TwapOrder>,
+
+    // Escrow holding the unsold input
+    #[account(
+        mut,
+        associated_token::mint = mint_in,
+        associated_token::authority = twap,
+    )]
+    pub escrow: Account<'info, TokenAccount>,
+
+    // Pool vaults for the input and output tokens
+    #[account(
+        mut,
+        associated_token::mint = mint_in,
+        associated_token::authority = config,
+    )]
+    pub vault_in: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = mint_out,
+        associated_token::authority = config,
+    )]
+    pub vault_out: Account<'info, TokenAccount>,
+
+    // Pool configuration - contains all the pool settings
+    #[account(
+        mut,
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    // Price feed - only needed for oracle-anchored pools
+    pub price_feed: Option<Account<'info, PriceFeed>>,
+
+    // Required program accounts
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> ExecuteTwapSlice<'info> {
+    // Sell the next slice against the pool, as long as it's due and meets the per-slice minimum
+    pub fn execute(&mut self) -> Result<()> {
+        // Check that the pool is not locked
+        require!(!self.config.locked, AmmError::PoolLocked);
+        self.config.check_direct_trading()?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let amount = self.twap.next_slice(self.escrow.amount, now)?;
+
+        // The output must be the pool's other token
+        let is_x = self.twap.is_x;
+        let mint_out = match is_x {
+            true => self.config.mint_y,
+            false => self.config.mint_x,
+        };
+        require_keys_eq!(self.mint_out.key(), mint_out, AmmError::InvalidToken);
+
+        // Read the oracle price if the pool is anchored to one
+        let (mint_x, mint_y) = match is_x {
+            true => (&self.mint_in, &self.mint_out),
+            false => (&self.mint_out, &self.mint_in),
+        };
+        let price = self.config.oracle_price(self.price_feed.as_ref(), mint_x.decimals, mint_y.decimals)?;
+
+        // Calculate the slice using the pool's pricing curve, exactly like a regular swap
+        let res = self.config.pool_swap(is_x, amount, price)?;
+        require!(res.withdraw >= self.twap.min_slice_out, AmmError::SlippageExceeded);
+        require!(res.withdraw != 0, AmmError::InvalidAmount);
+
+        self.deposit_tokens(res.deposit)?;
+        self.withdraw_tokens(res.withdraw)?;
+
+        // Close the order once every slice has executed
+        if self.twap.advance(now)? {
+            self.close_escrow()?;
+            self.twap.close(self.owner.to_account_info())?;
+        }
+
+        Ok(())
+    }
+
+    // Helper function to transfer a slice from escrow to pool vault
+    pub fn deposit_tokens(&self, amount: u64) -> Result<()> {
+        let twap = &self.twap;
+
+        // Create the authority seeds for the TWAP order account
+        let seed = twap.seed.to_le_bytes();
+        let seeds = &[
+            &b"twap"[..],
+            twap.config.as_ref(),
+            twap.owner.as_ref(),
+            &seed,
+            &[twap.twap_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        let accounts = Transfer {
+            from: self.escrow.to_account_info(),
+            to: self.vault_in.to_account_info(),
+            authority: twap.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), accounts, signer_seeds);
+
+        transfer(cpi_ctx, amount)
+    }
+
+    // Helper function to transfer a slice's output from pool vault to owner
+    pub fn withdraw_tokens(&self, amount: u64) -> Result<()> {
+        let cpi_program = self.token_program.to_account_info();
+
+        let accounts = Transfer {
+            from: self.vault_out.to_account_info(),
+            to: self.owner_out.to_account_info(),
+            authority: self.config.to_account_info(),
+        };
+
+        // Create the authority seeds for the config account
+        let seeds = &[
+            &b"config"[..],
+            &self.config.seed.to_le_bytes(),
+            &[self.config.config_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, accounts, signer_seeds);
+
+        transfer(cpi_ctx, amount)
+    }
+
+    // Helper function to close the emptied escrow, returning its rent to the owner
+    pub fn close_escrow(&self) -> Result<()> {
+        let twap = &self.twap;
+
+        // Create the authority seeds for the TWAP order account
+        let seed = twap.seed.to_le_bytes();
+        let seeds = &[
+            &b"twap"[..],
+            twap.config.as_ref(),
+            twap.owner.as_ref(),
+            &seed,
+            &[twap.twap_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        let accounts = CloseAccount {
+            account: self.escrow.to_account_info(),
+            destination: self.owner.to_account_info(),
+            authority: twap.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), accounts, signer_seeds);
+
+        close_account(cpi_ctx)
+    }
+}
@@ -1,8 +1,6 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{associated_token::AssociatedToken, token::{transfer, Burn, Mint, Token, TokenAccount, Transfer, burn}};
-use constant_product_curve::ConstantProduct;
-
-use crate::{errors::AmmError, state::Config};
+use crate::{errors::AmmError, math, state::{Config, LpPosition}};
 
 // This struct defines all the accounts needed to withdraw liquidity from the AMM
 // Users can burn their LP tokens to get back their original tokens
@@ -18,6 +16,7 @@ pub struct Withdraw<'info> {
     
     // Pool configuration - contains all the pool settings
     #[account(
+        mut,
         has_one = mint_x,
         has_one = mint_y,
         seeds = [b"config", config.seed.to_le_bytes().as_ref()],
@@ -70,6 +69,15 @@ pub struct Withdraw<'info> {
         associated_token::authority = config,
     )]
     pub user_lp: Account<'info, TokenAccount>,
+
+    // Depositor's position - only needed to track fee earnings for this depositor
+    #[account(
+        mut,
+        has_one = config,
+        seeds = [b"position", config.key().as_ref(), user.key().as_ref()],
+        bump = position.position_bump,
+    )]
+    pub position: Option<Account<'info, LpPosition>>,
     
     // Required program accounts
     pub token_program: Program<'info, Token>,
@@ -86,26 +94,52 @@ impl<'info> Withdraw<'info> {
         min_y: u64,  // Minimum amount of token Y that the user wants to receive
     ) -> Result<()> {
         // Check that the pool is not locked
-        require!(self.config.locked == false, AmmError::PoolLocked);
+        require!(!self.config.locked, AmmError::PoolLocked);
         require!(amount != 0, AmmError::InvalidAmount);
+
+        // Liquidity may be restricted to the pool authority (e.g. during a sale)
+        self.config.check_liquidity_provider(self.user.key())?;
+        self.config.check_position_required(self.position.is_some())?;
         require!(min_x != 0 || min_y != 0, AmmError::InvalidAmount);
 
+        self.config.check_minimum_liquidity(self.mint_lp.supply, amount)?;
+
         // Calculate how much of each token the user should receive
-        let amounts = ConstantProduct::xy_withdraw_amounts_from_l(
-            self.vault_x.amount, 
-            self.vault_y.amount, 
+        let amounts = math::withdraw_amounts(
+            self.config.reserve_x, 
+            self.config.reserve_y, 
             self.mint_lp.supply, 
             amount, 
-            6,
-        )
-        .map_err(AmmError::from)?;
+        )?;
+
+        // Withdrawing inside the pool's holding period leaves a penalty behind
+        let now = Clock::get()?.unix_timestamp;
+        let penalty_x = self.config.exit_penalty(self.position.as_deref(), amounts.x, now)?;
+        let penalty_y = self.config.exit_penalty(self.position.as_deref(), amounts.y, now)?;
+        let x = amounts.x - penalty_x;
+        let y = amounts.y - penalty_y;
 
         // Check that the calculated amounts meet the user's minimum requirements
-        require!(min_x <= amounts.x && min_y <= amounts.y, AmmError::SlippageExceeded);
+        require!(min_x <= x && min_y <= y, AmmError::SlippageExceeded);
+
+        // Keep the PMM equilibrium in step with the pool's liquidity
+        self.config.withdraw_targets(amount, self.mint_lp.supply)?;
+
+        // Track the burned LP for fee accounting
+        self.config.record_lp_burned(amount)?;
+        if let Some(position) = self.position.as_mut() {
+            position.settle(&self.config)?;
+            position.record_withdraw(x, y, amount)?;
+        }
+
+        // The penalty stays in the vaults and is shared by the remaining LPs
+        self.config.accrue_fees(true, penalty_x)?;
+        self.config.accrue_fees(false, penalty_y)?;
 
         // Transfer tokens from pool vaults to user
-        self.withdraw_tokens(true, amounts.x)?;  // Transfer token X
-        self.withdraw_tokens(false, amounts.y)?; // Transfer token Y
+        self.config.record_withdraw(x, y)?;
+        self.withdraw_tokens(true, x)?;  // Transfer token X
+        self.withdraw_tokens(false, y)?; // Transfer token Y
         
         // Burn the user's LP tokens
         self.burn_lp_tokens(amount)?;
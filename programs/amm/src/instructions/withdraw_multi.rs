@@ -0,0 +1,114 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{burn, transfer, Burn, Mint, Token, TokenAccount, Transfer};
+
+use crate::{errors::AmmError, state::MultiPool};
+
+// This struct defines all the accounts needed to withdraw from a multi-asset pool
+// Vaults and user token accounts are passed as remaining accounts in mint order:
+// [vault_0, user_0, vault_1, user_1, ...]
+#[derive(Accounts)]
+pub struct WithdrawMulti<'info> {
+    // The user who wants to remove liquidity
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // Pool account - contains the list of mints, reserves and pool settings
+    #[account(
+        mut,
+        seeds = [b"multi", pool.seed.to_le_bytes().as_ref()],
+        bump = pool.pool_bump,
+    )]
+    pub pool: Account<'info, MultiPool>,
+
+    // LP token mint - represents the user's share of the pool
+    #[account(
+        mut,
+        seeds = [b"lp", pool.key().as_ref()],
+        bump = pool.lp_bump,
+    )]
+    pub mint_lp: Account<'info, Mint>,
+
+    // User's LP token account - where their LP tokens are burned from
+    #[account(
+        mut,
+        associated_token::mint = mint_lp,
+        associated_token::authority = user,
+    )]
+    pub user_lp: Account<'info, TokenAccount>,
+
+    // Required program accounts
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> WithdrawMulti<'info> {
+    // Main withdraw function - removes liquidity in proportion to every vault
+    pub fn withdraw(
+        &mut self,
+        amount: u64,           // Amount of LP tokens that the user wants to "burn"
+        min_amounts: Vec<u64>, // Minimum amount of each token the user wants to receive
+        remaining_accounts: &'info [AccountInfo<'info>],
+    ) -> Result<()> {
+        // Check that the pool is not locked
+        require!(!self.pool.locked, AmmError::PoolLocked);
+        require!(amount != 0, AmmError::InvalidAmount);
+
+        let count = self.pool.mints.len();
+        require!(min_amounts.len() == count, AmmError::InvalidAmount);
+        require!(remaining_accounts.len() == count * 2, AmmError::InvalidAccountList);
+
+        // Share of each reserve owed to the user (rounded down)
+        let amounts = self.pool.withdraw_amounts(amount, self.mint_lp.supply)?;
+
+        let pool_key = self.pool.key();
+        for (i, accounts) in remaining_accounts.chunks(2).enumerate() {
+            let vault = self.pool.vault(pool_key, i, &accounts[0])?;
+            let user_token = self.pool.token_account(i, &accounts[1])?;
+
+            require!(amounts[i] >= min_amounts[i], AmmError::SlippageExceeded);
+
+            self.pool.record_withdraw(i, amounts[i])?;
+            self.withdraw_tokens(vault.to_account_info(), user_token.to_account_info(), amounts[i])?;
+        }
+
+        // Burn the user's LP tokens
+        self.burn_lp_tokens(amount)
+    }
+
+    // Helper function to transfer tokens from pool vault to user
+    pub fn withdraw_tokens(&self, from: AccountInfo<'info>, to: AccountInfo<'info>, amount: u64) -> Result<()> {
+        let cpi_program = self.token_program.to_account_info();
+
+        let cpi_accounts = Transfer {
+            from,
+            to,
+            authority: self.pool.to_account_info(),
+        };
+
+        // Create the authority seeds for the pool account
+        let seeds = &[
+            &b"multi"[..],
+            &self.pool.seed.to_le_bytes(),
+            &[self.pool.pool_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+        transfer(cpi_ctx, amount)
+    }
+
+    // Helper function to burn LP tokens from the user
+    pub fn burn_lp_tokens(&self, amount: u64) -> Result<()> {
+        let cpi_program = self.token_program.to_account_info();
+
+        let cpi_accounts = Burn {
+            mint: self.mint_lp.to_account_info(),
+            from: self.user_lp.to_account_info(),
+            authority: self.user.to_account_info(),
+        };
+
+        let cpi_context = CpiContext::new(cpi_program, cpi_accounts);
+
+        burn(cpi_context, amount)
+    }
+}
@@ -0,0 +1,210 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{associated_token::AssociatedToken, token::{transfer, Burn, Mint, Token, TokenAccount, Transfer, burn}};
+use crate::{errors::AmmError, math, state::{Config, LpPosition, PriceFeed}};
+
+// This struct defines all the accounts needed to withdraw liquidity as only one of the pool's tokens
+// The unwanted side of the withdrawal is swapped through the pool into the wanted one
+#[derive(Accounts)]
+pub struct WithdrawSingle<'info> {
+    // The user who wants to remove liquidity (pays for the transaction)
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // The two tokens that can be traded in this pool
+    pub mint_x: Account<'info, Mint>,
+    pub mint_y: Account<'info, Mint>,
+
+    // Pool configuration - contains all the pool settings
+    #[account(
+        mut,
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    // Price feed - only needed for oracle-anchored pools
+    pub price_feed: Option<Account<'info, PriceFeed>>,
+
+    // LP token mint - represents the user's share of the pool
+    #[account(
+        mut,
+        seeds = [b"lp", config.key().as_ref()],
+        bump = config.lp_bump,
+    )]
+    pub mint_lp: Account<'info, Mint>,
+
+    // Pool vaults - where the AMM stores the tokens
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = config,
+    )]
+    pub vault_x: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = config,
+    )]
+    pub vault_y: Account<'info, TokenAccount>,
+
+    // User's token accounts - where they receive their tokens back
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint_x,
+        associated_token::authority = user,
+    )]
+    pub user_x: Account<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint_y,
+        associated_token::authority = user,
+    )]
+    pub user_y: Account<'info, TokenAccount>,
+
+    // User's LP token account - where their LP tokens are burned from
+    #[account(
+        mut,
+        associated_token::mint = mint_lp,
+        associated_token::authority = user,
+    )]
+    pub user_lp: Account<'info, TokenAccount>,
+
+    // Depositor's position - only needed to track fee earnings for this depositor
+    #[account(
+        mut,
+        has_one = config,
+        seeds = [b"position", config.key().as_ref(), user.key().as_ref()],
+        bump = position.position_bump,
+    )]
+    pub position: Option<Account<'info, LpPosition>>,
+
+    // Required program accounts
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> WithdrawSingle<'info> {
+    // Burn `amount` LP for its proportional share like `withdraw`, then swap the other token
+    // into X (or Y) inside the pool, paying the swap fee
+    pub fn withdraw_single(&mut self, is_x: bool, amount: u64, min_out: u64) -> Result<()> {
+        // Check that the pool is not locked
+        require!(!self.config.locked, AmmError::PoolLocked);
+        self.config.check_direct_trading()?;
+        require!(amount != 0, AmmError::InvalidAmount);
+
+        // Liquidity may be restricted to the pool authority (e.g. during a sale)
+        self.config.check_liquidity_provider(self.user.key())?;
+        self.config.check_position_required(self.position.is_some())?;
+
+        // Calculate how much of each token the LP is worth, exactly like `withdraw`
+        let supply = self.mint_lp.supply;
+        self.config.check_minimum_liquidity(supply, amount)?;
+        let amounts = math::withdraw_amounts(self.config.reserve_x, self.config.reserve_y, supply, amount)?;
+
+        // Keep the PMM equilibrium in step with the pool's liquidity
+        self.config.withdraw_targets(amount, supply)?;
+
+        // Read the oracle price if the pool is anchored to one
+        let price = self.config.oracle_price(
+            self.price_feed.as_ref(),
+            self.mint_x.decimals,
+            self.mint_y.decimals,
+        )?;
+
+        // Withdrawing inside the pool's holding period leaves a penalty behind
+        let now = Clock::get()?.unix_timestamp;
+        let penalty_x = self.config.exit_penalty(self.position.as_deref(), amounts.x, now)?;
+        let penalty_y = self.config.exit_penalty(self.position.as_deref(), amounts.y, now)?;
+        let x = amounts.x - penalty_x;
+        let y = amounts.y - penalty_y;
+
+        // Stop the burned LP earning fees before the swap, so its fee goes to the remaining LPs
+        self.config.record_lp_burned(amount)?;
+        if let Some(position) = self.position.as_mut() {
+            position.settle(&self.config)?;
+        }
+
+        // The penalty stays in the vaults and is shared by the remaining LPs
+        self.config.accrue_fees(true, penalty_x)?;
+        self.config.accrue_fees(false, penalty_y)?;
+
+        // Sell the unwanted side to what's left in the pool after the withdrawal
+        self.config.record_withdraw(x, y)?;
+        let (wanted, unwanted) = match is_x {
+            true => (x, y),
+            false => (y, x),
+        };
+        let swapped = match unwanted {
+            0 => 0,
+            _ => self.config.pool_swap(!is_x, unwanted, price)?.withdraw,
+        };
+
+        // Check that the combined output meets the user's minimum
+        let total = wanted.checked_add(swapped).ok_or(AmmError::Overflow)?;
+        require!(total != 0, AmmError::InvalidAmount);
+        require!(total >= min_out, AmmError::SlippageExceeded);
+
+        // Record what the user actually received, all in the wanted token
+        if let Some(position) = self.position.as_mut() {
+            let (x, y) = match is_x {
+                true => (total, 0),
+                false => (0, total),
+            };
+            position.record_withdraw(x, y, amount)?;
+        }
+
+        // The unwanted side never leaves the vault, so only the wanted token moves
+        self.withdraw_tokens(is_x, total)?;
+
+        // Burn the user's LP tokens
+        self.burn_lp_tokens(amount)
+    }
+
+    // Helper function to transfer tokens from pool vault to user
+    pub fn withdraw_tokens(&self, is_x: bool, amount: u64) -> Result<()> {
+        let (from, to) = match is_x {
+            true => (self.vault_x.to_account_info(), self.user_x.to_account_info()),
+            false => (self.vault_y.to_account_info(), self.user_y.to_account_info()),
+        };
+
+        let cpi_program = self.token_program.to_account_info();
+
+        let cpi_accounts = Transfer {
+            from,
+            to,
+            authority: self.config.to_account_info(),
+        };
+
+        // Create the authority seeds for the config account
+        let seeds = &[
+            &b"config"[..],
+            &self.config.seed.to_le_bytes(),
+            &[self.config.config_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        let ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+        transfer(ctx, amount)
+    }
+
+    // Helper function to burn LP tokens from the user
+    pub fn burn_lp_tokens(&self, amount: u64) -> Result<()> {
+        let cpi_program = self.token_program.to_account_info();
+
+        let cpi_accounts = Burn {
+            mint: self.mint_lp.to_account_info(),
+            from: self.user_lp.to_account_info(),
+            authority: self.user.to_account_info(),
+        };
+
+        let ctx = CpiContext::new(cpi_program, cpi_accounts);
+
+        burn(ctx, amount)
+    }
+}
@@ -4,7 +4,9 @@ use anchor_lang::prelude::*;
 
 // Import our custom modules
 mod errors;
+pub mod math;
 pub mod state;
+mod signature;
 mod instructions;
 
 use instructions::*;
@@ -12,31 +14,347 @@ use instructions::*;
 // This is the program ID - a unique identifier for our AMM program
 declare_id!("71Qrt2waYnddGjzb9jubTtjgiDBWSfy3cFPdeKHWrFhX");
 
-#[program]
-pub mod amm {
+// Anchor generates the IDL instruction handlers next to the program module, and they still call
+// the deprecated `AccountInfo::realloc`, so the program sits in a module that allows just that
+#[allow(deprecated)]
+mod amm_program {
     use super::*;
 
-    // Initialize a new AMM (Automated Market Maker)
-    // Creates the pool with two tokens and sets up the initial configuration
-    pub fn initialize(ctx: Context<Initialize>, seed: u64, fee: u16, authority: Option<Pubkey>) -> Result<()> {
-        ctx.accounts.init(seed, fee, authority, ctx.bumps)
-    }
+    #[program]
+    pub mod amm {
+        use super::*;
 
-    // Add liquidity to the pool
-    // Users can deposit both tokens to earn trading fees
-    pub fn deposit(ctx: Context<Deposit>, amount: u64, max_x: u64, max_y: u64) -> Result<()> {
-        ctx.accounts.deposit(amount, max_x, max_y)
-    }
+        // Initialize a new AMM (Automated Market Maker)
+        // Creates the pool with two tokens and sets up the initial configuration
+        pub fn initialize(ctx: Context<Initialize>, seed: u64, fee: u16, authority: Option<Pubkey>) -> Result<()> {
+            ctx.accounts.init(seed, fee, authority, ctx.bumps)
+        }
 
-    // Remove liquidity from the pool
-    // Users can withdraw their tokens and LP tokens
-    pub fn withdraw(ctx: Context<Withdraw>, amount: u64, max_x: u64, max_y: u64) -> Result<()> {
-        ctx.accounts.withdraw(amount, max_x, max_y)
-    }
+        // Initialize a new oracle-anchored AMM
+        // Swaps are priced around the price feed's price instead of the pool's own ratio
+        pub fn initialize_oracle(ctx: Context<Initialize>, seed: u64, fee: u16, authority: Option<Pubkey>, params: OracleParams) -> Result<()> {
+            ctx.accounts.init(seed, fee, authority, ctx.bumps)?;
+            ctx.accounts.init_oracle(params)
+        }
+
+        // Initialize a new liquidity bootstrapping pool
+        // Token weights shift over the sale window, and only the authority can add or remove liquidity until it ends
+        pub fn initialize_lbp(ctx: Context<Initialize>, seed: u64, fee: u16, authority: Option<Pubkey>, params: LbpParams) -> Result<()> {
+            ctx.accounts.init(seed, fee, authority, ctx.bumps)?;
+            ctx.accounts.init_lbp(params)
+        }
+
+        // Initialize a new bonding curve launch selling X for Y
+        // The creator funds it with the launch supply; once the raise target is hit, `graduate`
+        // turns it into a constant product pool with its LP permanently locked
+        pub fn initialize_launch(ctx: Context<Initialize>, seed: u64, fee: u16, authority: Option<Pubkey>, params: LaunchParams) -> Result<()> {
+            ctx.accounts.init(seed, fee, authority, ctx.bumps)?;
+            ctx.accounts.init_launch(params)
+        }
+
+        // Add liquidity to the pool
+        // Users can deposit both tokens to earn trading fees
+        pub fn deposit(ctx: Context<Deposit>, amount: u64, max_x: u64, max_y: u64) -> Result<()> {
+            ctx.accounts.deposit(amount, max_x, max_y)
+        }
+
+        // Remove liquidity from the pool
+        // Users can withdraw their tokens and LP tokens
+        pub fn withdraw(ctx: Context<Withdraw>, amount: u64, max_x: u64, max_y: u64) -> Result<()> {
+            ctx.accounts.withdraw(amount, max_x, max_y)
+        }
+
+        // Swap one token for another
+        // Users can trade tokens using the AMM's pricing formula
+        pub fn swap(ctx: Context<Swap>, is_x: bool, amount_in: u64, min_amount_out: u64) -> Result<()> {
+            ctx.accounts.swap(is_x, amount_in, min_amount_out)
+        }
+
+        // Create a price feed that oracle-anchored pools can trade against
+        pub fn create_price_feed(ctx: Context<CreatePriceFeed>, expo: i32) -> Result<()> {
+            ctx.accounts.create(expo)
+        }
+
+        // Publish a new price to a price feed
+        pub fn update_price_feed(ctx: Context<UpdatePriceFeed>, price: u64, conf: u64, publish_time: i64) -> Result<()> {
+            ctx.accounts.update(price, conf, publish_time)
+        }
+
+        // Initialize a multi-asset pool holding two or more tokens
+        // Mints and vaults are passed as remaining accounts: [mint_0, vault_0, mint_1, vault_1, ...]
+        pub fn initialize_multi<'info>(ctx: Context<'_, '_, 'info, 'info, InitializeMulti<'info>>, seed: u64, fee: u16, authority: Option<Pubkey>) -> Result<()> {
+            ctx.accounts.init(seed, fee, authority, ctx.bumps, ctx.remaining_accounts)
+        }
+
+        // Add liquidity to a multi-asset pool in proportion to every vault
+        // Remaining accounts: [vault_0, user_0, vault_1, user_1, ...]
+        pub fn deposit_multi<'info>(ctx: Context<'_, '_, 'info, 'info, DepositMulti<'info>>, amount: u64, max_amounts: Vec<u64>) -> Result<()> {
+            ctx.accounts.deposit(amount, max_amounts, ctx.remaining_accounts)
+        }
+
+        // Remove liquidity from a multi-asset pool in proportion to every vault
+        // Remaining accounts: [vault_0, user_0, vault_1, user_1, ...]
+        pub fn withdraw_multi<'info>(ctx: Context<'_, '_, 'info, 'info, WithdrawMulti<'info>>, amount: u64, min_amounts: Vec<u64>) -> Result<()> {
+            ctx.accounts.withdraw(amount, min_amounts, ctx.remaining_accounts)
+        }
+
+        // Swap any two tokens held by a multi-asset pool
+        // Remaining accounts: [vault_in, user_in, vault_out, user_out]
+        pub fn swap_multi<'info>(ctx: Context<'_, '_, 'info, 'info, SwapMulti<'info>>, index_in: u8, index_out: u8, amount_in: u64, min_amount_out: u64) -> Result<()> {
+            ctx.accounts.swap(index_in, index_out, amount_in, min_amount_out, ctx.remaining_accounts)
+        }
+
+        // Swap through several pools in a row (e.g. A -> B -> C) with one slippage check
+        // Each hop's pool accounts are passed as remaining accounts, in route order
+        pub fn swap_route<'info>(ctx: Context<'_, '_, 'info, 'info, SwapRoute<'info>>, amount_in: u64, min_amount_out: u64) -> Result<()> {
+            ctx.accounts.swap_route(amount_in, min_amount_out, ctx.remaining_accounts)
+        }
+
+        // Split one swap across several pools of the same pair with one slippage check
+        // Pass an empty `amounts` to split so marginal prices are equal, or one amount per pool
+        pub fn swap_split<'info>(ctx: Context<'_, '_, 'info, 'info, SwapSplit<'info>>, is_x: bool, amount_in: u64, min_amount_out: u64, amounts: Vec<u64>) -> Result<()> {
+            ctx.accounts.swap_split(is_x, amount_in, min_amount_out, amounts, ctx.remaining_accounts)
+        }
+
+        // Borrow tokens from one of the pool's vaults for the rest of the transaction
+        // A `flash_repay` for the same pool must come later in the transaction, or this fails
+        pub fn flash_borrow(ctx: Context<FlashLoan>, is_x: bool, amount: u64) -> Result<()> {
+            ctx.accounts.borrow(is_x, amount)
+        }
+
+        // Repay the flash loan in progress plus its fee
+        // The fee stays in the vault, so it accrues to LPs
+        pub fn flash_repay(ctx: Context<FlashLoan>) -> Result<()> {
+            ctx.accounts.repay()
+        }
+
+        // Change the pool's flash loan fee
+        // Only the pool authority can do this
+        pub fn set_flash_fee(ctx: Context<SetFlashFee>, fee: u16) -> Result<()> {
+            ctx.accounts.set_flash_fee(fee)
+        }
+
+        // Swap with the output sent before the input is paid
+        // The callback program is invoked with `data` followed by the swap details, and must pay the input vault
+        pub fn flash_swap<'info>(ctx: Context<'_, '_, 'info, 'info, FlashSwap<'info>>, is_x: bool, amount_in: u64, min_amount_out: u64, data: Vec<u8>) -> Result<()> {
+            ctx.accounts.flash_swap(is_x, amount_in, min_amount_out, data, ctx.remaining_accounts)
+        }
+
+        // Place a limit order selling `amount` of one token for at least `min_amount_out` of the other
+        // The input is escrowed until a cranker fills the order or the owner cancels it
+        pub fn place_order(ctx: Context<PlaceOrder>, seed: u64, amount: u64, min_amount_out: u64, bounty: u16) -> Result<()> {
+            ctx.accounts.place(seed, amount, min_amount_out, bounty, ctx.bumps)
+        }
+
+        // Cancel a limit order and refund its escrow
+        pub fn cancel_order(ctx: Context<CancelOrder>) -> Result<()> {
+            ctx.accounts.cancel()
+        }
+
+        // Fill the passed limit orders whose price the pool now meets
+        // Anyone can crank this and earns each filled order's bounty
+        pub fn fill_orders<'info>(ctx: Context<'_, '_, 'info, 'info, FillOrders<'info>>) -> Result<()> {
+            ctx.accounts.fill_orders(ctx.remaining_accounts)
+        }
+
+        // Place a TWAP order selling `slices` equal slices of `slice_amount`, one per `interval` seconds
+        // Each slice must fetch at least `min_slice_out`
+        pub fn place_twap(ctx: Context<PlaceTwap>, seed: u64, slice_amount: u64, slices: u16, interval: i64, min_slice_out: u64) -> Result<()> {
+            ctx.accounts.place(seed, slice_amount, slices, interval, min_slice_out, ctx.bumps)
+        }
+
+        // Cancel a TWAP order and refund the unsold input
+        pub fn cancel_twap(ctx: Context<CancelTwap>) -> Result<()> {
+            ctx.accounts.cancel()
+        }
+
+        // Execute the next slice of a TWAP order once its interval has passed
+        // Anyone can act as the keeper
+        pub fn execute_twap_slice(ctx: Context<ExecuteTwapSlice>) -> Result<()> {
+            ctx.accounts.execute()
+        }
+
+        // Submit a swap intent to the pool's current batch auction
+        // The input is escrowed until the batch settles at one uniform price
+        pub fn submit_intent(ctx: Context<SubmitIntent>, is_x: bool, amount_in: u64, min_amount_out: u64) -> Result<()> {
+            ctx.accounts.submit(is_x, amount_in, min_amount_out, ctx.bumps)
+        }
+
+        // Settle a batch auction once its window has ended
+        // Anyone can settle; only the imbalance between the two sides trades against the pool
+        pub fn settle_batch(ctx: Context<SettleBatch>) -> Result<()> {
+            ctx.accounts.settle()
+        }
+
+        // Claim an intent's output from a settled batch, or its input back if it didn't fill
+        pub fn claim_batch(ctx: Context<ClaimBatch>, index: u8) -> Result<()> {
+            ctx.accounts.claim(index)
+        }
 
-    // Swap one token for another
-    // Users can trade tokens using the AMM's pricing formula
-    pub fn swap(ctx: Context<Swap>, is_x: bool, amount_in: u64, min_amount_out: u64) -> Result<()> {
-        ctx.accounts.swap(is_x, amount_in, min_amount_out)
+        // Turn batch mode on (batch length in slots) or off (0), with the smallest intent of each token
+        // Only the pool authority can do this
+        pub fn set_batch_mode(ctx: Context<SetBatchMode>, batch_slots: u64, min_intent_x: u64, min_intent_y: u64) -> Result<()> {
+            ctx.accounts.set_batch_mode(batch_slots, min_intent_x, min_intent_y)
+        }
+
+        // Commit to a swap by its hash, escrowing the input (and optionally the other token to hide the direction)
+        // The hash is over (is_x, amount_in, min_amount_out, salt), see `SwapCommit::commitment`
+        pub fn commit_swap(ctx: Context<CommitSwap>, seed: u64, hash: [u8; 32], deposit_x: u64, deposit_y: u64) -> Result<()> {
+            ctx.accounts.commit(seed, hash, deposit_x, deposit_y, ctx.bumps)
+        }
+
+        // Reveal a committed swap after the minimum delay and execute it
+        // Unused escrow is refunded and the commit is closed
+        pub fn reveal_swap(ctx: Context<RevealSwap>, is_x: bool, amount_in: u64, min_amount_out: u64, salt: [u8; 32]) -> Result<()> {
+            ctx.accounts.reveal(is_x, amount_in, min_amount_out, salt)
+        }
+
+        // Reclaim the escrow of a commit that was never revealed, once it has expired
+        pub fn reclaim_commit(ctx: Context<ReclaimCommit>) -> Result<()> {
+            ctx.accounts.reclaim()
+        }
+
+        // Execute a swap signed off-chain by a user, on their behalf
+        // The relayer pays the fees and can earn the intent's tip; the signature is checked by a preceding ed25519 instruction
+        pub fn execute_intent(ctx: Context<ExecuteIntent>, intent: SwapIntent) -> Result<()> {
+            ctx.accounts.execute(intent, ctx.bumps)
+        }
+
+        // Fill a swap from an allowed market maker's signed quote, with any remainder going through the curve
+        // The quote's signature is checked by a preceding ed25519 instruction
+        pub fn fill_rfq(ctx: Context<FillRfq>, quote: RfqQuote, amount_in: u64, min_amount_out: u64) -> Result<()> {
+            ctx.accounts.fill(quote, amount_in, min_amount_out)
+        }
+
+        // Replace the pool's allowlist of RFQ market makers
+        // Only the pool authority can do this
+        pub fn set_market_makers(ctx: Context<SetMarketMakers>, market_makers: Vec<Pubkey>) -> Result<()> {
+            ctx.accounts.set_market_makers(market_makers)
+        }
+
+        // Add liquidity with only token X or only token Y
+        // Part of the deposit is swapped through the pool first, paying the swap fee
+        pub fn deposit_single(ctx: Context<DepositSingle>, is_x: bool, amount: u64, min_lp_out: u64) -> Result<()> {
+            ctx.accounts.deposit_single(is_x, amount, min_lp_out)
+        }
+
+        // Remove liquidity as only token X or only token Y
+        // The other side of the withdrawal is swapped through the pool, paying the swap fee
+        pub fn withdraw_single(ctx: Context<WithdrawSingle>, is_x: bool, amount: u64, min_out: u64) -> Result<()> {
+            ctx.accounts.withdraw_single(is_x, amount, min_out)
+        }
+
+        // Add liquidity by an exact amount of token X or Y instead of an LP amount
+        // The matching amount of the other token and the LP minted are worked out on-chain
+        pub fn deposit_exact(ctx: Context<Deposit>, is_x: bool, amount: u64, max_other: u64, min_lp: u64) -> Result<()> {
+            ctx.accounts.deposit_exact(is_x, amount, max_other, min_lp)
+        }
+
+        // Set the pool's reserves to what its vaults hold, counting any tokens sent to them directly
+        // Only the authority can do this unless the pool allows anyone to
+        pub fn sync(ctx: Context<SyncReserves>) -> Result<()> {
+            ctx.accounts.sync()
+        }
+
+        // Send whatever the vaults hold beyond the pool's reserves to a recipient
+        // Only the pool authority can do this
+        pub fn skim(ctx: Context<SkimReserves>) -> Result<()> {
+            ctx.accounts.skim()
+        }
+
+        // Choose whether anyone can sync the pool, or only the authority
+        pub fn set_sync_mode(ctx: Context<SetSyncMode>, permissionless: bool) -> Result<()> {
+            ctx.accounts.set_sync_mode(permissionless)
+        }
+
+        // Lock LP tokens until a set time, after which only the beneficiary can unlock them
+        // The lock account serves as a public certificate of the locked liquidity
+        pub fn lock_liquidity(ctx: Context<LockLiquidity>, seed: u64, amount: u64, unlock_time: i64, beneficiary: Pubkey) -> Result<()> {
+            ctx.accounts.lock(seed, amount, unlock_time, beneficiary, ctx.bumps)
+        }
+
+        // Release time-locked LP tokens to the beneficiary once the lock expires
+        pub fn unlock(ctx: Context<UnlockLiquidity>) -> Result<()> {
+            ctx.accounts.unlock()
+        }
+
+        // Read the total LP tokens locked for a pool, in time-locks or forever (view only)
+        pub fn locked_liquidity(ctx: Context<LockedLiquidity>) -> Result<u64> {
+            ctx.accounts.locked_liquidity()
+        }
+
+        // Lock LP tokens forever by sending them to the pool's lock PDA
+        // The pool records the amount so it's still counted as liquidity
+        pub fn burn_lock(ctx: Context<BurnLock>, amount: u64) -> Result<()> {
+            ctx.accounts.burn_lock(amount)
+        }
+
+        // Open an LP farm for the pool
+        // Only the pool authority can do this
+        pub fn create_farm(ctx: Context<CreateFarm>) -> Result<()> {
+            ctx.accounts.create_farm(ctx.bumps)
+        }
+
+        // Add a reward token to the pool's farm, emitted per second once funded
+        // Only the pool authority can do this
+        pub fn add_farm_reward(ctx: Context<AddFarmReward>, emission_rate: u64) -> Result<()> {
+            ctx.accounts.add_reward(emission_rate)
+        }
+
+        // Fund one of a farm's reward tokens
+        pub fn fund_rewards(ctx: Context<FundRewards>, amount: u64) -> Result<()> {
+            ctx.accounts.fund_rewards(amount)
+        }
+
+        // Stake LP tokens in the pool's farm to earn its rewards
+        pub fn stake(ctx: Context<Stake>, amount: u64) -> Result<()> {
+            ctx.accounts.stake(amount, ctx.bumps)
+        }
+
+        // Unstake LP tokens from the pool's farm, keeping the rewards earned so far
+        pub fn unstake(ctx: Context<Stake>, amount: u64) -> Result<()> {
+            ctx.accounts.unstake(amount)
+        }
+
+        // Claim everything earned of one of a farm's reward tokens
+        pub fn claim(ctx: Context<ClaimRewards>) -> Result<()> {
+            ctx.accounts.claim()
+        }
+
+        // Open a position that tracks the caller's deposits, withdrawals and fee earnings in a pool
+        pub fn open_position(ctx: Context<OpenPosition>) -> Result<()> {
+            ctx.accounts.open_position(ctx.bumps)
+        }
+
+        // Set how long LP must be held after a deposit and the penalty for withdrawing sooner
+        // Only the pool authority can do this
+        pub fn set_hold_period(ctx: Context<SetHoldPeriod>, min_hold: i64, early_exit_fee: u16) -> Result<()> {
+            ctx.accounts.set_hold_period(min_hold, early_exit_fee)
+        }
+
+        // Cap the pool's reserves, LP supply and per-wallet LP reachable through deposits (0 = no cap)
+        // Only the pool authority can do this
+        pub fn set_deposit_caps(ctx: Context<SetDepositCaps>, max_reserve_x: u64, max_reserve_y: u64, max_lp_supply: u64, max_wallet_lp: u64) -> Result<()> {
+            ctx.accounts.set_deposit_caps(max_reserve_x, max_reserve_y, max_lp_supply, max_wallet_lp)
+        }
+
+        // Graduate a launch that hit its raise target into a constant product pool
+        // Anyone can call this; the unsold X the pool doesn't need goes back to the launch creator
+        pub fn graduate(ctx: Context<Graduate>) -> Result<()> {
+            ctx.accounts.graduate()
+        }
+
+        // Grow a pool created with the original config layout to the current one
+        // Anyone can call this; the payer covers the extra rent
+        pub fn migrate_config(ctx: Context<MigrateConfig>, seed: u64) -> Result<()> {
+            ctx.accounts.migrate_config(seed)
+        }
+
+        // Unstake all of the caller's LP from the pool's farm without updating it, giving up any rewards owed
+        pub fn emergency_unstake(ctx: Context<Stake>) -> Result<()> {
+            ctx.accounts.emergency_unstake()
+        }
     }
-}
\ No newline at end of file
+}
+
+pub use amm_program::*;
@@ -0,0 +1,152 @@
+// Uniform-price clearing for batch auctions
+// X sellers and Y sellers are first matched against each other, and only the side left over
+// trades against the pool. The pool trade is sized so the matched part clears at the same
+// average price the pool pays, which leaves everyone in the batch with one price:
+// (total_x - amount_in) * amount_out / amount_in = total_y when X is the side left over
+
+use anchor_lang::prelude::*;
+
+// Result of clearing a batch
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatchClearing {
+    pub is_x: bool, // Whether the leftover traded against the pool is X
+    pub amount_in: u64, // Leftover sold to the pool (0 when the batch matches internally)
+    pub amount_out: u64, // Pool output for the leftover
+    pub x_out: u64, // Total X shared between Y sellers
+    pub y_out: u64, // Total Y shared between X sellers
+}
+
+// Clear a batch selling `total_x` X and `total_y` Y, where `quote(is_x, amount_in)` returns
+// the pool's output for selling `amount_in` (or an error if it can't take it)
+pub fn clear_batch<F>(total_x: u64, total_y: u64, mut quote: F) -> Result<BatchClearing>
+where
+    F: FnMut(bool, u64) -> Result<u64>,
+{
+    let (amount_in, amount_out) = net_amount(total_x, total_y, |amount| quote(true, amount));
+    if amount_in != 0 {
+        return Ok(BatchClearing {
+            is_x: true,
+            amount_in,
+            amount_out,
+            x_out: total_x - amount_in,
+            y_out: total_y.saturating_add(amount_out),
+        });
+    }
+
+    let (amount_in, amount_out) = net_amount(total_y, total_x, |amount| quote(false, amount));
+    if amount_in != 0 {
+        return Ok(BatchClearing {
+            is_x: false,
+            amount_in,
+            amount_out,
+            x_out: total_x.saturating_add(amount_out),
+            y_out: total_y - amount_in,
+        });
+    }
+
+    // Neither side beats the pool's price on its own, so the batch simply swaps between
+    // its two sides at total_y / total_x, which lies inside the pool's bid-ask spread
+    Ok(BatchClearing {
+        is_x: true,
+        amount_in: 0,
+        amount_out: 0,
+        x_out: total_x,
+        y_out: total_y,
+    })
+}
+
+// Find the largest amount d of the `sold` side to trade against the pool such that
+// what's left, (sold - d), still fetches at least the pool's average price d -> out(d)
+// when matched against `other`: (sold - d) * out(d) >= other * d
+fn net_amount<F>(sold: u64, other: u64, mut quote: F) -> (u64, u64)
+where
+    F: FnMut(u64) -> Result<u64>,
+{
+    let (mut low, mut high) = (0u64, sold);
+    let mut best_out = 0u64;
+
+    while low < high {
+        let mid = high - (high - low) / 2;
+        match quote(mid) {
+            Ok(out) if (sold - mid) as u128 * out as u128 >= other as u128 * mid as u128 => {
+                low = mid;
+                best_out = out;
+            }
+            _ => high = mid - 1,
+        }
+    }
+
+    match low {
+        0 => (0, 0),
+        _ => (low, best_out),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::swap_amount_out;
+
+    fn constant_product(x: u64, y: u64, fee: u16) -> impl FnMut(bool, u64) -> Result<u64> {
+        move |is_x, amount| match is_x {
+            true => Ok(swap_amount_out(x, y, amount, fee)?.withdraw),
+            false => Ok(swap_amount_out(y, x, amount, fee)?.withdraw),
+        }
+    }
+
+    #[test]
+    fn one_sided_batch_trades_everything_against_the_pool() {
+        let res = clear_batch(10_000, 0, constant_product(1_000_000, 1_000_000, 30)).unwrap();
+        let out = swap_amount_out(1_000_000, 1_000_000, 10_000, 30).unwrap().withdraw;
+        assert_eq!(res, BatchClearing { is_x: true, amount_in: 10_000, amount_out: out, x_out: 0, y_out: out });
+
+        let res = clear_batch(0, 10_000, constant_product(1_000_000, 1_000_000, 30)).unwrap();
+        assert_eq!(res, BatchClearing { is_x: false, amount_in: 10_000, amount_out: out, x_out: out, y_out: 0 });
+    }
+
+    #[test]
+    fn balanced_batch_matches_internally() {
+        let res = clear_batch(5_000, 5_000, constant_product(1_000_000, 1_000_000, 30)).unwrap();
+        assert_eq!(res.amount_in, 0);
+        assert_eq!((res.x_out, res.y_out), (5_000, 5_000));
+    }
+
+    #[test]
+    fn both_sides_clear_at_one_price() {
+        let res = clear_batch(30_000, 10_000, constant_product(1_000_000, 1_000_000, 30)).unwrap();
+        assert!(res.is_x);
+        assert!(res.amount_in > 0 && res.amount_in < 30_000);
+
+        // X sellers get y_out for 30_000 X, Y sellers get x_out for 10_000 Y; the two prices agree
+        let x_sellers_price = res.y_out as f64 / 30_000.0;
+        let y_sellers_price = 10_000.0 / res.x_out as f64;
+        assert!((x_sellers_price - y_sellers_price).abs() / x_sellers_price < 1e-3);
+
+        // Which is better for X sellers than trading alone against the pool
+        let alone = swap_amount_out(1_000_000, 1_000_000, 30_000, 30).unwrap().withdraw;
+        assert!(res.y_out > alone);
+    }
+
+    #[test]
+    fn clearing_conserves_tokens() {
+        for (total_x, total_y) in [(1u64, 0u64), (123_456, 7_890), (7_890, 123_456), (999_999, 1), (50_000, 49_000)] {
+            let res = clear_batch(total_x, total_y, constant_product(2_000_000, 1_000_000, 25)).unwrap();
+            match res.is_x {
+                true => {
+                    assert_eq!(res.x_out + res.amount_in, total_x);
+                    assert_eq!(res.y_out, total_y + res.amount_out);
+                }
+                false => {
+                    assert_eq!(res.y_out + res.amount_in, total_y);
+                    assert_eq!(res.x_out, total_x + res.amount_out);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn empty_batch_clears_to_nothing() {
+        let res = clear_batch(0, 0, constant_product(1_000, 1_000, 0)).unwrap();
+        assert_eq!((res.amount_in, res.x_out, res.y_out), (0, 0, 0));
+    }
+}
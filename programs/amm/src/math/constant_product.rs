@@ -0,0 +1,303 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::AmmError;
+
+use super::{div_ceil_u128, mul_div_ceil, mul_div_floor, to_u128, to_u64, FEE_DENOMINATOR, U256};
+
+// Result of a swap calculation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SwapResult {
+    pub deposit: u64,  // Amount the user pays into the pool (including the fee)
+    pub withdraw: u64, // Amount the user receives from the pool
+    pub fee: u64,      // Part of the deposit kept by the pool as a fee
+}
+
+// A pair of token amounts, one for each side of the pool
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct XYAmounts {
+    pub x: u64,
+    pub y: u64,
+}
+
+// Calculate the fee charged on an input amount (rounded up)
+pub fn fee_amount(amount: u64, fee: u16) -> Result<u64> {
+    require!(fee as u64 <= FEE_DENOMINATOR, AmmError::InvalidFee);
+    mul_div_ceil(amount, fee as u64, FEE_DENOMINATOR)
+}
+
+// Calculate the output of a constant product (x * y = k) swap
+// The fee is taken from the input, and the new output reserve is rounded up
+// so the invariant never decreases
+pub fn swap_amount_out(reserve_in: u64, reserve_out: u64, amount_in: u64, fee: u16) -> Result<SwapResult> {
+    require!(reserve_in != 0 && reserve_out != 0, AmmError::ZeroBalance);
+    require!(amount_in != 0, AmmError::InvalidAmount);
+
+    let fee_paid = fee_amount(amount_in, fee)?;
+    let amount_in_after_fee = amount_in.checked_sub(fee_paid).ok_or(AmmError::Underflow)?;
+
+    let k = (reserve_in as u128)
+        .checked_mul(reserve_out as u128)
+        .ok_or(AmmError::Overflow)?;
+    let new_reserve_in = (reserve_in as u128)
+        .checked_add(amount_in_after_fee as u128)
+        .ok_or(AmmError::Overflow)?;
+    let new_reserve_out = div_ceil_u128(k, new_reserve_in)?;
+
+    let withdraw = (reserve_out as u128)
+        .checked_sub(new_reserve_out)
+        .ok_or(AmmError::Underflow)?;
+
+    Ok(SwapResult {
+        deposit: amount_in,
+        withdraw: to_u64(withdraw)?,
+        fee: fee_paid,
+    })
+}
+
+// Calculate how much of each token is needed to mint `amount` LP tokens (rounded up)
+pub fn deposit_amounts(x: u64, y: u64, supply: u64, amount: u64) -> Result<XYAmounts> {
+    require!(supply != 0, AmmError::NoLiquidityInPool);
+
+    Ok(XYAmounts {
+        x: mul_div_ceil(x, amount, supply)?,
+        y: mul_div_ceil(y, amount, supply)?,
+    })
+}
+
+// Calculate the LP minted (rounded down) and the matching amount of the other token (rounded up)
+// for depositing exactly `amount` of the token held in `reserve_in`
+pub fn exact_deposit_amounts(reserve_in: u64, reserve_other: u64, supply: u64, amount: u64) -> Result<(u64, u64)> {
+    require!(supply != 0, AmmError::NoLiquidityInPool);
+
+    let lp = mul_div_floor(amount, supply, reserve_in)?;
+    let other = mul_div_ceil(reserve_other, amount, reserve_in)?;
+    Ok((lp, other))
+}
+
+// Calculate how much of each token is returned for burning `amount` LP tokens (rounded down)
+pub fn withdraw_amounts(x: u64, y: u64, supply: u64, amount: u64) -> Result<XYAmounts> {
+    require!(supply != 0, AmmError::NoLiquidityInPool);
+    require!(amount <= supply, AmmError::InsufficientBalance);
+
+    Ok(XYAmounts {
+        x: mul_div_floor(x, amount, supply)?,
+        y: mul_div_floor(y, amount, supply)?,
+    })
+}
+
+// Calculate the LP minted for the first deposit into a pool: sqrt(x * y), rounded down
+pub fn initial_liquidity(x: u64, y: u64) -> Result<u64> {
+    let product = U256::from(x) * U256::from(y);
+    to_u64(to_u128(product.integer_sqrt())?)
+}
+
+// Work out the constant product pool a launch graduates into: keep only as much X as matches
+// the curve's final price (y + virtual y) / x, so trading continues from the same price
+// Returns the X kept (rounded down) and the LP for the whole pool
+pub fn graduation_liquidity(reserve_x: u64, reserve_y: u64, virtual_y: u64) -> Result<(u64, u64)> {
+    let virtual_reserve_y = reserve_y.checked_add(virtual_y).ok_or(AmmError::Overflow)?;
+    let keep_x = mul_div_floor(reserve_x, reserve_y, virtual_reserve_y)?;
+    require!(keep_x != 0, AmmError::NoLiquidityInPool);
+
+    let liquidity = initial_liquidity(keep_x, reserve_y)?;
+    require!(liquidity != 0, AmmError::NoLiquidityInPool);
+
+    Ok((keep_x, liquidity))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Reference implementation of the previous external curve (fee rounded up,
+    // new output reserve rounded down) so outputs can be compared directly
+    fn previous_swap_out(x: u64, y: u64, a: u64, fee: u16) -> u64 {
+        let after_fee = a as u128 * (10_000 - fee as u128) / 10_000;
+        let k = x as u128 * y as u128;
+        (y as u128 - k / (x as u128 + after_fee)) as u64
+    }
+
+    #[test]
+    fn fee_is_rounded_up() {
+        assert_eq!(fee_amount(10_000, 30).unwrap(), 30);
+        assert_eq!(fee_amount(1, 30).unwrap(), 1);
+        assert_eq!(fee_amount(333, 30).unwrap(), 1);
+        assert_eq!(fee_amount(3_334, 30).unwrap(), 11);
+        assert_eq!(fee_amount(1_000, 0).unwrap(), 0);
+        assert_eq!(fee_amount(1_000, 10_000).unwrap(), 1_000);
+        assert_eq!(fee_amount(1_000, 10_001).unwrap_err(), AmmError::InvalidFee.into());
+    }
+
+    #[test]
+    fn swap_matches_previous_curve_on_exact_divisions() {
+        // 1000 * 1000 / (1000 + 1000) is exact, so both roundings agree
+        let res = swap_amount_out(1_000, 1_000, 1_000, 0).unwrap();
+        assert_eq!(res, SwapResult { deposit: 1_000, withdraw: 500, fee: 0 });
+        assert_eq!(res.withdraw, previous_swap_out(1_000, 1_000, 1_000, 0));
+
+        let res = swap_amount_out(20, 30, 10, 0).unwrap();
+        assert_eq!(res.withdraw, 10);
+        assert_eq!(res.withdraw, previous_swap_out(20, 30, 10, 0));
+    }
+
+    #[test]
+    fn swap_never_pays_more_than_previous_curve() {
+        let reserves = [1u64, 7, 1_000, 123_456, 1_000_000_000, u32::MAX as u64, u64::MAX / 2];
+        let amounts = [1u64, 3, 999, 50_000, 1_000_000_000_000];
+        let fees = [0u16, 1, 25, 30, 500, 9_999];
+
+        for &x in reserves.iter() {
+            for &y in reserves.iter() {
+                for &a in amounts.iter() {
+                    for &fee in fees.iter() {
+                        let Ok(res) = swap_amount_out(x, y, a, fee) else {
+                            continue;
+                        };
+                        let previous = previous_swap_out(x, y, a, fee);
+                        // Output is rounded against the user by at most one unit
+                        assert!(res.withdraw <= previous);
+                        assert!(previous - res.withdraw <= 1);
+                        // The fee matches the previous curve exactly
+                        assert_eq!(res.fee, a - (a as u128 * (10_000 - fee as u128) / 10_000) as u64);
+                        // The invariant never decreases
+                        let k_before = x as u128 * y as u128;
+                        let k_after = (x as u128 + (a - res.fee) as u128) * (y - res.withdraw) as u128;
+                        assert!(k_after >= k_before);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn swap_rounds_in_pool_favor() {
+        // k = 10_000, new x = 103, k / 103 = 97.08 -> pool keeps 98, user gets 2
+        let res = swap_amount_out(100, 100, 3, 0).unwrap();
+        assert_eq!(res.withdraw, 2);
+        assert_eq!(previous_swap_out(100, 100, 3, 0), 3);
+    }
+
+    #[test]
+    fn swap_charges_fee_on_input() {
+        let res = swap_amount_out(1_000_000, 1_000_000, 10_000, 30).unwrap();
+        assert_eq!(res.deposit, 10_000);
+        assert_eq!(res.fee, 30);
+        // 1_000_000 - ceil(1e12 / 1_009_970) = 1_000_000 - 990_129
+        assert_eq!(res.withdraw, 9_871);
+    }
+
+    #[test]
+    fn swap_rejects_empty_pool_and_zero_input() {
+        assert_eq!(swap_amount_out(0, 1_000, 10, 30).unwrap_err(), AmmError::ZeroBalance.into());
+        assert_eq!(swap_amount_out(1_000, 0, 10, 30).unwrap_err(), AmmError::ZeroBalance.into());
+        assert_eq!(swap_amount_out(1_000, 1_000, 0, 30).unwrap_err(), AmmError::InvalidAmount.into());
+        assert_eq!(swap_amount_out(1_000, 1_000, 10, 10_001).unwrap_err(), AmmError::InvalidFee.into());
+    }
+
+    #[test]
+    fn swap_handles_full_range_without_overflow() {
+        let res = swap_amount_out(u64::MAX, u64::MAX, u64::MAX, 0).unwrap();
+        // Doubling the input reserve halves the output reserve (rounded up)
+        assert_eq!(res.withdraw, u64::MAX / 2);
+    }
+
+    #[test]
+    fn deposit_matches_previous_curve_for_exact_ratios() {
+        // Previous curve: ratio = (l + a) * p / l, deposit = x * ratio / p - x
+        assert_eq!(deposit_amounts(1_000, 2_000, 100, 100).unwrap(), XYAmounts { x: 1_000, y: 2_000 });
+        assert_eq!(deposit_amounts(1_000, 2_000, 100, 50).unwrap(), XYAmounts { x: 500, y: 1_000 });
+        assert_eq!(deposit_amounts(1_000, 2_000, 100, 300).unwrap(), XYAmounts { x: 3_000, y: 6_000 });
+    }
+
+    #[test]
+    fn deposit_rounds_up() {
+        // The previous curve truncated the ratio to the given precision and
+        // could let a depositor mint LP for free; now every partial unit is charged
+        assert_eq!(deposit_amounts(1_000, 2_000, 100, 10).unwrap(), XYAmounts { x: 100, y: 200 });
+        assert_eq!(deposit_amounts(10, 10, 3, 1).unwrap(), XYAmounts { x: 4, y: 4 });
+        assert_eq!(deposit_amounts(1, 1, 1_000_000, 1).unwrap(), XYAmounts { x: 1, y: 1 });
+    }
+
+    #[test]
+    fn deposit_requires_existing_liquidity() {
+        assert_eq!(deposit_amounts(1_000, 1_000, 0, 10).unwrap_err(), AmmError::NoLiquidityInPool.into());
+        assert_eq!(deposit_amounts(u64::MAX, 1, 1, 2).unwrap_err(), AmmError::Overflow.into());
+    }
+
+    #[test]
+    fn exact_deposit_rounds_against_depositor() {
+        assert_eq!(exact_deposit_amounts(1_000, 2_000, 100, 300).unwrap(), (30, 600));
+        assert_eq!(exact_deposit_amounts(10, 10, 3, 4).unwrap(), (1, 4));
+        assert_eq!(exact_deposit_amounts(3, 7, 5, 1).unwrap(), (1, 3));
+        assert_eq!(exact_deposit_amounts(1_000, 1_000, 0, 10).unwrap_err(), AmmError::NoLiquidityInPool.into());
+
+        // Withdrawing the LP straight away never returns more than was put in
+        for &(x, y, supply) in [(1_000u64, 2_000u64, 100u64), (7, 13, 3), (123_456, 654_321, 99_999)].iter() {
+            for amount in [1u64, 2, 5, 50, 1_000] {
+                let (lp, other) = exact_deposit_amounts(x, y, supply, amount).unwrap();
+                let wd = withdraw_amounts(x + amount, y + other, supply + lp, lp).unwrap();
+                assert!(wd.x <= amount);
+                assert!(wd.y <= other);
+            }
+        }
+    }
+
+    #[test]
+    fn withdraw_matches_previous_curve_for_exact_ratios() {
+        assert_eq!(withdraw_amounts(1_000, 2_000, 100, 100).unwrap(), XYAmounts { x: 1_000, y: 2_000 });
+        assert_eq!(withdraw_amounts(1_000, 2_000, 100, 50).unwrap(), XYAmounts { x: 500, y: 1_000 });
+        assert_eq!(withdraw_amounts(1_000, 2_000, 100, 25).unwrap(), XYAmounts { x: 250, y: 500 });
+    }
+
+    #[test]
+    fn withdraw_rounds_down() {
+        assert_eq!(withdraw_amounts(10, 10, 3, 1).unwrap(), XYAmounts { x: 3, y: 3 });
+        assert_eq!(withdraw_amounts(1, 1, 1_000_000, 1).unwrap(), XYAmounts { x: 0, y: 0 });
+        assert_eq!(withdraw_amounts(u64::MAX, u64::MAX, u64::MAX, u64::MAX - 1).unwrap().x, u64::MAX - 1);
+    }
+
+    #[test]
+    fn withdraw_rejects_more_than_supply() {
+        assert_eq!(withdraw_amounts(1_000, 1_000, 100, 101).unwrap_err(), AmmError::InsufficientBalance.into());
+        assert_eq!(withdraw_amounts(1_000, 1_000, 0, 1).unwrap_err(), AmmError::NoLiquidityInPool.into());
+    }
+
+    #[test]
+    fn deposit_then_withdraw_never_profits() {
+        let states = [(1_000u64, 2_000u64, 100u64), (7, 13, 3), (123_456, 654_321, 99_999), (u32::MAX as u64, 5, 17)];
+        for &(x, y, supply) in states.iter() {
+            for amount in [1u64, 2, 5, 50, 1_000] {
+                let dep = deposit_amounts(x, y, supply, amount).unwrap();
+                let wd = withdraw_amounts(x + dep.x, y + dep.y, supply + amount, amount).unwrap();
+                assert!(wd.x <= dep.x);
+                assert!(wd.y <= dep.y);
+            }
+        }
+    }
+
+    #[test]
+    fn initial_liquidity_is_geometric_mean() {
+        assert_eq!(initial_liquidity(100, 400).unwrap(), 200);
+        assert_eq!(initial_liquidity(10, 10).unwrap(), 10);
+        assert_eq!(initial_liquidity(2, 3).unwrap(), 2);
+        assert_eq!(initial_liquidity(0, 1_000).unwrap(), 0);
+        assert_eq!(initial_liquidity(u64::MAX, u64::MAX).unwrap(), u64::MAX);
+    }
+
+    #[test]
+    fn graduation_keeps_the_final_price() {
+        // 800 X left against 300 raised and 100 virtual Y: price 0.5 Y per X
+        let (keep_x, liquidity) = graduation_liquidity(800, 300, 100).unwrap();
+        assert_eq!(keep_x, 600);
+        assert_eq!(liquidity, initial_liquidity(600, 300).unwrap());
+        // Rounding keeps less X, so the price never drops
+        assert_eq!(graduation_liquidity(1_000, 1, 2).unwrap().0, 333);
+    }
+
+    #[test]
+    fn graduation_needs_liquidity() {
+        assert!(graduation_liquidity(800, 0, 100).is_err());
+        assert!(graduation_liquidity(0, 300, 100).is_err());
+        assert!(graduation_liquidity(1, 1, u64::MAX - 1).is_err());
+    }
+}
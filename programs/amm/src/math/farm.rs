@@ -0,0 +1,78 @@
+// Reward accounting for LP farms
+// Each reward keeps a running total of rewards paid per staked LP token (WAD). A staker's
+// earnings are their stake times how much that total grew since they last settled, rounded
+// down, so the sum paid out can never exceed what was emitted
+
+use anchor_lang::prelude::*;
+
+use crate::errors::AmmError;
+
+use super::{mul_div_floor_u128, to_u64, WAD};
+
+// Rewards emitted over `elapsed` seconds at `rate` per second, capped by the funding left
+pub fn emitted_rewards(rate: u64, elapsed: i64, remaining: u64) -> u64 {
+    let elapsed = elapsed.max(0) as u128;
+    (rate as u128).saturating_mul(elapsed).min(remaining as u128) as u64
+}
+
+// Reward per staked LP token (WAD) after sharing `emitted` rewards between `total_staked` tokens,
+// rounded down
+pub fn reward_per_share(current: u128, emitted: u64, total_staked: u64) -> Result<u128> {
+    require!(total_staked != 0, AmmError::ZeroBalance);
+
+    let added = mul_div_floor_u128(emitted as u128, WAD, total_staked as u128)?;
+    current.checked_add(added).ok_or(error!(AmmError::Overflow))
+}
+
+// Rewards earned by `amount` staked LP tokens while the reward per share grew from `paid` to `current`,
+// rounded down
+pub fn earned_rewards(amount: u64, current: u128, paid: u128) -> Result<u64> {
+    let growth = current.checked_sub(paid).ok_or(AmmError::Underflow)?;
+    to_u64(mul_div_floor_u128(amount as u128, growth, WAD)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn emission_is_capped_by_funding() {
+        assert_eq!(emitted_rewards(10, 5, 1_000), 50);
+        assert_eq!(emitted_rewards(10, 500, 1_000), 1_000);
+        assert_eq!(emitted_rewards(u64::MAX, i64::MAX, 7), 7);
+        assert_eq!(emitted_rewards(10, -5, 1_000), 0);
+    }
+
+    #[test]
+    fn stakers_share_rewards_pro_rata() {
+        // 1_000 rewards over 300 staked: a 100 stake earns a third, a 200 stake two thirds
+        let rps = reward_per_share(0, 1_000, 300).unwrap();
+        assert_eq!(earned_rewards(100, rps, 0).unwrap(), 333);
+        assert_eq!(earned_rewards(200, rps, 0).unwrap(), 666);
+
+        // A staker who settled halfway only earns the growth since then
+        let later = reward_per_share(rps, 1_000, 300).unwrap();
+        assert_eq!(earned_rewards(100, later, rps).unwrap(), 333);
+    }
+
+    #[test]
+    fn payouts_never_exceed_emissions() {
+        let stakes = [1u64, 7, 13, 999, 123_456];
+        let total: u64 = stakes.iter().sum();
+        let mut rps = 0u128;
+        let mut emitted = 0u64;
+        for amount in [1u64, 17, 1_000, 5, 999_999] {
+            rps = reward_per_share(rps, amount, total).unwrap();
+            emitted += amount;
+        }
+
+        let paid: u64 = stakes.iter().map(|&stake| earned_rewards(stake, rps, 0).unwrap()).sum();
+        assert!(paid <= emitted);
+    }
+
+    #[test]
+    fn empty_farm_cannot_share_rewards() {
+        assert_eq!(reward_per_share(0, 1, 0).unwrap_err(), AmmError::ZeroBalance.into());
+        assert_eq!(earned_rewards(1, 0, 1).unwrap_err(), AmmError::Underflow.into());
+    }
+}
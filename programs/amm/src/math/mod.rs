@@ -0,0 +1,121 @@
+// This module contains all the pricing math used by the AMM
+// Every function uses checked arithmetic and rounds explicitly in the pool's favor,
+// so a user can never extract more than their share through rounding dust
+
+use anchor_lang::prelude::*;
+
+use crate::errors::AmmError;
+
+pub mod constant_product;
+pub mod pmm;
+pub mod weighted;
+pub mod split;
+pub mod batch;
+pub mod zap;
+pub mod farm;
+
+pub use constant_product::*;
+
+// 256-bit unsigned integer for intermediate products of two u128 values
+// Kept in its own module so the macro doesn't pick up Anchor's `Result` alias
+mod u256 {
+    #![allow(clippy::assign_op_pattern, clippy::ptr_offset_with_cast, clippy::manual_div_ceil)]
+    uint::construct_uint! {
+        pub struct U256(4);
+    }
+}
+
+pub use u256::U256;
+
+// Fees are expressed in basis points (10_000 = 100%)
+pub const FEE_DENOMINATOR: u64 = 10_000;
+
+// Fixed-point scale for prices, weights and other fractional values
+pub const WAD: u128 = 1_000_000_000_000_000_000;
+
+// Narrow a u128 back into a u64, failing instead of truncating
+pub fn to_u64(value: u128) -> Result<u64> {
+    u64::try_from(value).map_err(|_| error!(AmmError::Overflow))
+}
+
+// Narrow a U256 back into a u128, failing instead of truncating
+pub fn to_u128(value: U256) -> Result<u128> {
+    require!(value <= U256::from(u128::MAX), AmmError::Overflow);
+    Ok(value.as_u128())
+}
+
+// Calculate a * b / c, rounding down
+pub fn mul_div_floor(a: u64, b: u64, c: u64) -> Result<u64> {
+    require!(c != 0, AmmError::ZeroBalance);
+    let product = (a as u128).checked_mul(b as u128).ok_or(AmmError::Overflow)?;
+    to_u64(product / c as u128)
+}
+
+// Calculate a * b / c, rounding up
+pub fn mul_div_ceil(a: u64, b: u64, c: u64) -> Result<u64> {
+    require!(c != 0, AmmError::ZeroBalance);
+    let product = (a as u128).checked_mul(b as u128).ok_or(AmmError::Overflow)?;
+    to_u64(div_ceil_u128(product, c as u128)?)
+}
+
+// Calculate a * b / c on u128 operands, rounding down
+pub fn mul_div_floor_u128(a: u128, b: u128, c: u128) -> Result<u128> {
+    require!(c != 0, AmmError::ZeroBalance);
+    let product = U256::from(a) * U256::from(b);
+    to_u128(product / U256::from(c))
+}
+
+// Calculate a * b / c on u128 operands, rounding up
+pub fn mul_div_ceil_u128(a: u128, b: u128, c: u128) -> Result<u128> {
+    require!(c != 0, AmmError::ZeroBalance);
+    let product = U256::from(a) * U256::from(b);
+    let c = U256::from(c);
+    let quotient = product / c;
+    match (product % c).is_zero() {
+        true => to_u128(quotient),
+        false => to_u128(quotient + U256::one()),
+    }
+}
+
+// Calculate a / b, rounding up
+pub fn div_ceil_u128(a: u128, b: u128) -> Result<u128> {
+    require!(b != 0, AmmError::ZeroBalance);
+    let quotient = a / b;
+    match a % b {
+        0 => Ok(quotient),
+        _ => quotient.checked_add(1).ok_or(error!(AmmError::Overflow)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mul_div_rounds_in_requested_direction() {
+        assert_eq!(mul_div_floor(10, 10, 3).unwrap(), 33);
+        assert_eq!(mul_div_ceil(10, 10, 3).unwrap(), 34);
+        assert_eq!(mul_div_floor(10, 9, 3).unwrap(), 30);
+        assert_eq!(mul_div_ceil(10, 9, 3).unwrap(), 30);
+        assert_eq!(mul_div_floor(u64::MAX, u64::MAX, u64::MAX).unwrap(), u64::MAX);
+        assert_eq!(mul_div_ceil(u64::MAX, 2, 3).unwrap(), 12_297_829_382_473_034_410);
+    }
+
+    #[test]
+    fn mul_div_u128_uses_wide_intermediates() {
+        assert_eq!(mul_div_floor_u128(u128::MAX, u128::MAX, u128::MAX).unwrap(), u128::MAX);
+        assert_eq!(mul_div_floor_u128(u128::MAX, 2, 3).unwrap(), u128::MAX / 3 * 2);
+        assert_eq!(mul_div_floor_u128(u128::MAX, 1, 2).unwrap(), u128::MAX / 2);
+        assert_eq!(mul_div_ceil_u128(u128::MAX, 1, 2).unwrap(), u128::MAX / 2 + 1);
+        assert_eq!(mul_div_ceil_u128(6, 4, 8).unwrap(), 3);
+    }
+
+    #[test]
+    fn checked_helpers_map_errors() {
+        assert_eq!(mul_div_floor(1, 1, 0).unwrap_err(), AmmError::ZeroBalance.into());
+        assert_eq!(mul_div_ceil(u64::MAX, 2, 1).unwrap_err(), AmmError::Overflow.into());
+        assert_eq!(mul_div_floor_u128(u128::MAX, 2, 1).unwrap_err(), AmmError::Overflow.into());
+        assert_eq!(div_ceil_u128(1, 0).unwrap_err(), AmmError::ZeroBalance.into());
+        assert_eq!(to_u64(u64::MAX as u128 + 1).unwrap_err(), AmmError::Overflow.into());
+    }
+}
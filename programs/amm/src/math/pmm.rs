@@ -0,0 +1,384 @@
+// Proactive market maker (PMM) math for oracle-anchored pools
+// Prices start at the oracle price and slide away from it as the pool's reserves
+// move away from their targets. The slippage parameter `k` (WAD-scaled, 0..=1)
+// controls how concentrated liquidity is: k = 0 trades at exactly the oracle price,
+// k = 1 behaves like a constant product curve centered on the oracle price.
+
+use anchor_lang::prelude::*;
+
+use crate::errors::AmmError;
+
+use super::{fee_amount, to_u128, to_u64, SwapResult, FEE_DENOMINATOR, U256, WAD};
+
+// State of one side of a PMM pool before and after a swap
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PmmReserves {
+    pub reserve_in: u64,  // Current reserve of the token being sold to the pool
+    pub target_in: u64,   // Equilibrium reserve of the token being sold to the pool
+    pub reserve_out: u64, // Current reserve of the token being bought from the pool
+    pub target_out: u64,  // Equilibrium reserve of the token being bought from the pool
+}
+
+// Result of a PMM swap, including the re-anchored targets to store
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PmmSwapResult {
+    pub result: SwapResult,
+    pub target_in: u64,
+    pub target_out: u64,
+}
+
+// Convert a basis point value into a WAD
+pub fn bps_to_wad(bps: u16) -> Result<u128> {
+    require!(bps as u64 <= FEE_DENOMINATOR, AmmError::InvalidAmount);
+    Ok(bps as u128 * (WAD / FEE_DENOMINATOR as u128))
+}
+
+// Convert an oracle price for one whole X in whole Y into raw Y units per raw X unit (WAD)
+pub fn oracle_price_wad(price: u64, expo: i32, decimals_x: u8, decimals_y: u8) -> Result<u128> {
+    require!(price != 0, AmmError::InvalidPrice);
+
+    let exponent = 18i64 + expo as i64 + decimals_y as i64 - decimals_x as i64;
+    require!(exponent.abs() <= 38, AmmError::InvalidPrice);
+
+    let scale = 10u128.pow(exponent.unsigned_abs() as u32);
+    let wad = match exponent >= 0 {
+        true => (price as u128).checked_mul(scale).ok_or(AmmError::Overflow)?,
+        false => price as u128 / scale,
+    };
+    require!(wad != 0, AmmError::InvalidPrice);
+
+    Ok(wad)
+}
+
+// Invert a WAD price (rounded down so the pool never overpays)
+pub fn inverse_price_wad(price: u128) -> Result<u128> {
+    require!(price != 0, AmmError::InvalidPrice);
+    to_u128(U256::from(WAD) * U256::from(WAD) / U256::from(price))
+}
+
+// Swap `amount_in` against a PMM pool whose price is `price` (out units per in unit, WAD)
+pub fn swap_amount_out(reserves: PmmReserves, amount_in: u64, fee: u16, price: u128, k: u128) -> Result<PmmSwapResult> {
+    require!(reserves.reserve_in != 0 && reserves.reserve_out != 0, AmmError::ZeroBalance);
+    require!(amount_in != 0, AmmError::InvalidAmount);
+    require!(k <= WAD, AmmError::InvalidAmount);
+
+    let fee_paid = fee_amount(amount_in, fee)?;
+    let amount_in_after_fee = amount_in.checked_sub(fee_paid).ok_or(AmmError::Underflow)?;
+
+    // Re-anchor the targets to the current oracle price before trading
+    let reserves = adjust_targets(reserves, price, k)?;
+    let withdraw = sell_amount_out(reserves, amount_in_after_fee, price, k)?;
+
+    // Never let a swap empty one side of the pool
+    require!(withdraw < reserves.reserve_out, AmmError::InsufficientBalance);
+
+    Ok(PmmSwapResult {
+        result: SwapResult {
+            deposit: amount_in,
+            withdraw,
+            fee: fee_paid,
+        },
+        target_in: reserves.target_in,
+        target_out: reserves.target_out,
+    })
+}
+
+// Recalculate the target of whichever side is short, so that the surplus on the
+// other side is worth exactly the integral back to equilibrium at the current price
+pub fn adjust_targets(reserves: PmmReserves, price: u128, k: u128) -> Result<PmmReserves> {
+    let mut adjusted = reserves;
+
+    if reserves.reserve_in < reserves.target_in {
+        // Surplus of the output token, converted into input token units
+        let surplus = reserves.reserve_out.saturating_sub(reserves.target_out);
+        let surplus = U256::from(surplus) * U256::from(WAD) / U256::from(price);
+        adjusted.target_in = solve_target(reserves.reserve_in, to_u64(to_u128(surplus)?)?, k)?;
+    } else if reserves.reserve_out < reserves.target_out {
+        // Surplus of the input token, converted into output token units
+        let surplus = reserves.reserve_in - reserves.target_in;
+        let surplus = U256::from(surplus) * U256::from(price) / U256::from(WAD);
+        adjusted.target_out = solve_target(reserves.reserve_out, to_u64(to_u128(surplus)?)?, k)?;
+    } else {
+        // Neither side is short (e.g. fees have accrued), so the pool is at a new equilibrium
+        adjusted.target_in = reserves.reserve_in;
+        adjusted.target_out = reserves.reserve_out;
+    }
+
+    Ok(adjusted)
+}
+
+// Calculate the output for selling `amount` into a pool with already adjusted targets
+fn sell_amount_out(reserves: PmmReserves, amount: u64, price: u128, k: u128) -> Result<u64> {
+    let PmmReserves { reserve_in, target_in, reserve_out, target_out } = reserves;
+
+    // The output side is short or at its target: price slides along the output side's curve
+    if reserve_in >= target_in {
+        let fair = fair_amount(amount, price)?;
+        let new_reserve_out = solve_trade(target_out, reserve_out, fair, k)?;
+        return reserve_out.checked_sub(new_reserve_out).ok_or(error!(AmmError::Underflow));
+    }
+
+    // The input side is short: selling it first moves the pool back towards equilibrium
+    let back_to_target_in = target_in - reserve_in;
+    let back_to_target_out = reserve_out.saturating_sub(target_out);
+
+    if amount < back_to_target_in {
+        let new_reserve_in = reserve_in.checked_add(amount).ok_or(AmmError::Overflow)?;
+        let out = integrate(target_in, new_reserve_in, reserve_in, price, k)?;
+        return Ok(out.min(back_to_target_out));
+    }
+
+    // Cross equilibrium, then continue selling with the output side becoming short
+    let remaining = amount - back_to_target_in;
+    let fair = fair_amount(remaining, price)?;
+    let new_reserve_out = solve_trade(target_out, target_out, fair, k)?;
+    let out = target_out.checked_sub(new_reserve_out).ok_or(AmmError::Underflow)?;
+
+    back_to_target_out.checked_add(out).ok_or(error!(AmmError::Overflow))
+}
+
+// Value of `amount` input tokens in output tokens at the oracle price (rounded down)
+fn fair_amount(amount: u64, price: u128) -> Result<u64> {
+    let fair = U256::from(amount) * U256::from(price) / U256::from(WAD);
+    to_u64(to_u128(fair)?)
+}
+
+// Output received when the short side moves from v2 up to v1 (v2 < v1 <= v0):
+// price * (v1 - v2) * (1 - k + k * v0^2 / (v1 * v2)), rounded down
+fn integrate(v0: u64, v1: u64, v2: u64, price: u128, k: u128) -> Result<u64> {
+    require!(v2 != 0, AmmError::ZeroBalance);
+
+    let v0_squared = U256::from(v0) * U256::from(v0);
+    let penalty = U256::from(k)
+        .checked_mul(v0_squared)
+        .ok_or(AmmError::Overflow)?
+        / (U256::from(v1) * U256::from(v2));
+    let factor = U256::from(WAD - k) + penalty;
+
+    let out = U256::from(price)
+        .checked_mul(U256::from(v1 - v2))
+        .and_then(|value| value.checked_mul(factor))
+        .ok_or(AmmError::Overflow)?
+        / U256::from(WAD)
+        / U256::from(WAD);
+
+    to_u64(to_u128(out)?)
+}
+
+// Solve for the new reserve v2 of the short side after paying out `delta` worth of
+// fair value, starting from reserve v1 with target v0. Rounded up so the pool keeps dust.
+// Derived from delta = (v1 - v2) * (1 - k) + k * v0^2 * (1 / v2 - 1 / v1), i.e.
+// (1 - k) * v2^2 + (delta - (1 - k) * v1 + k * v0^2 / v1) * v2 - k * v0^2 = 0
+fn solve_trade(v0: u64, v1: u64, delta: u64, k: u128) -> Result<u64> {
+    require!(v1 != 0, AmmError::ZeroBalance);
+
+    if delta == 0 {
+        return Ok(v1);
+    }
+
+    if k == 0 {
+        return Ok(v1.saturating_sub(delta));
+    }
+
+    let v0_squared = U256::from(v0) * U256::from(v0);
+    let k_v0_squared = U256::from(k).checked_mul(v0_squared).ok_or(AmmError::Overflow)?;
+
+    if k == WAD {
+        // Linear case: v2 = v0^2 * v1 / (delta * v1 + v0^2)
+        let numerator = v0_squared * U256::from(v1);
+        let denominator = U256::from(delta) * U256::from(v1) + v0_squared;
+        return to_u64(to_u128(div_ceil(numerator, denominator))?);
+    }
+
+    // Every coefficient is scaled by WAD
+    let a = U256::from(WAD - k);
+    let b_positive = U256::from(WAD) * U256::from(delta) + div_ceil(k_v0_squared, U256::from(v1));
+    let b_negative = a * U256::from(v1);
+
+    let discriminant = U256::from(4u8)
+        .checked_mul(a)
+        .and_then(|value| value.checked_mul(k_v0_squared))
+        .ok_or(AmmError::Overflow)?;
+
+    let numerator = match b_positive >= b_negative {
+        true => {
+            let b = b_positive - b_negative;
+            let root = ceil_sqrt(b.checked_mul(b).and_then(|b2| b2.checked_add(discriminant)).ok_or(AmmError::Overflow)?);
+            root - b
+        }
+        false => {
+            let b = b_negative - b_positive;
+            let root = ceil_sqrt(b.checked_mul(b).and_then(|b2| b2.checked_add(discriminant)).ok_or(AmmError::Overflow)?);
+            root + b
+        }
+    };
+
+    let v2 = to_u64(to_u128(div_ceil(numerator, a * U256::from(2u8)))?)?;
+    Ok(v2.min(v1))
+}
+
+// Solve for the target v0 of the short side with reserve v1, given the other side's
+// surplus worth `delta` of this side's token:
+// v0 = v1 + v1 * (sqrt(1 + 4 * k * delta / v1) - 1) / (2 * k), rounded down
+fn solve_target(v1: u64, delta: u64, k: u128) -> Result<u64> {
+    if delta == 0 {
+        return Ok(v1);
+    }
+
+    if k == 0 {
+        return v1.checked_add(delta).ok_or(error!(AmmError::Overflow));
+    }
+
+    require!(v1 != 0, AmmError::ZeroBalance);
+
+    let wad = U256::from(WAD);
+    let inner = wad * wad + U256::from(4u8) * U256::from(k) * U256::from(delta) * wad / U256::from(v1);
+    let root = inner.integer_sqrt();
+    let growth = U256::from(v1) * (root - wad) / (U256::from(2u8) * U256::from(k));
+
+    let v0 = U256::from(v1) + growth;
+    to_u64(to_u128(v0)?)
+}
+
+// Divide two U256 values, rounding up
+fn div_ceil(a: U256, b: U256) -> U256 {
+    let quotient = a / b;
+    match (a % b).is_zero() {
+        true => quotient,
+        false => quotient + U256::one(),
+    }
+}
+
+// Integer square root, rounding up
+fn ceil_sqrt(value: U256) -> U256 {
+    let root = value.integer_sqrt();
+    match root * root == value {
+        true => root,
+        false => root + U256::one(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PRICE_ONE: u128 = WAD;
+
+    fn balanced(reserve: u64) -> PmmReserves {
+        PmmReserves {
+            reserve_in: reserve,
+            target_in: reserve,
+            reserve_out: reserve,
+            target_out: reserve,
+        }
+    }
+
+    #[test]
+    fn oracle_price_accounts_for_decimals() {
+        // 1 X = 25 Y, both with 6 decimals
+        assert_eq!(oracle_price_wad(25, 0, 6, 6).unwrap(), 25 * WAD);
+        // 1 X = 1.5 Y expressed as 150 * 10^-2
+        assert_eq!(oracle_price_wad(150, -2, 6, 6).unwrap(), 15 * WAD / 10);
+        // X has 9 decimals, Y has 6: one raw X unit is worth 10^-3 raw Y units at price 1
+        assert_eq!(oracle_price_wad(1, 0, 9, 6).unwrap(), WAD / 1_000);
+        assert_eq!(oracle_price_wad(0, 0, 6, 6).unwrap_err(), AmmError::InvalidPrice.into());
+        assert_eq!(oracle_price_wad(1, -40, 6, 6).unwrap_err(), AmmError::InvalidPrice.into());
+    }
+
+    #[test]
+    fn zero_k_trades_at_oracle_price() {
+        let res = swap_amount_out(balanced(1_000_000), 1_000, 0, 2 * PRICE_ONE, 0).unwrap();
+        assert_eq!(res.result.withdraw, 2_000);
+        assert_eq!(res.target_in, 1_000_000);
+        assert_eq!(res.target_out, 1_000_000);
+    }
+
+    #[test]
+    fn full_k_matches_constant_product_around_oracle_price() {
+        // With k = 1 and the pool at equilibrium, out = q * d / (q + d) where d = price * amount
+        let res = swap_amount_out(balanced(1_000_000), 10_000, 0, PRICE_ONE, WAD).unwrap();
+        let constant_product = super::super::swap_amount_out(1_000_000, 1_000_000, 10_000, 0).unwrap();
+        assert_eq!(res.result.withdraw, constant_product.withdraw);
+    }
+
+    #[test]
+    fn price_slides_with_k() {
+        let low_k = swap_amount_out(balanced(1_000_000), 100_000, 0, PRICE_ONE, WAD / 10).unwrap();
+        let high_k = swap_amount_out(balanced(1_000_000), 100_000, 0, PRICE_ONE, WAD / 2).unwrap();
+        // More concentrated liquidity means less slippage
+        assert!(low_k.result.withdraw > high_k.result.withdraw);
+        assert!(low_k.result.withdraw < 100_000);
+    }
+
+    #[test]
+    fn general_k_matches_closed_form() {
+        // k = 0.5, v0 = v1 = 1000, delta = 100:
+        // 0.5 * v2^2 + (100 - 500 + 500) * v2 - 500_000 = 0 -> v2 = (-100 + sqrt(10_000 + 1_000_000)) = 904.987...
+        assert_eq!(solve_trade(1_000, 1_000, 100, WAD / 2).unwrap(), 905);
+    }
+
+    #[test]
+    fn fee_is_taken_before_pricing() {
+        let no_fee = swap_amount_out(balanced(1_000_000), 10_000, 0, PRICE_ONE, WAD / 10).unwrap();
+        let with_fee = swap_amount_out(balanced(1_000_000), 10_000, 30, PRICE_ONE, WAD / 10).unwrap();
+        assert_eq!(with_fee.result.fee, 30);
+        assert!(with_fee.result.withdraw < no_fee.result.withdraw);
+    }
+
+    #[test]
+    fn selling_the_short_side_earns_a_premium() {
+        // The input side is short: the pool pays above the oracle price to restore it
+        let reserves = PmmReserves {
+            reserve_in: 900_000,
+            target_in: 1_000_000,
+            reserve_out: 1_100_000,
+            target_out: 1_000_000,
+        };
+        let res = swap_amount_out(reserves, 10_000, 0, PRICE_ONE, WAD / 2).unwrap();
+        assert!(res.result.withdraw > 10_000);
+    }
+
+    #[test]
+    fn round_trip_never_profits() {
+        let k = WAD / 4;
+        let first = swap_amount_out(balanced(1_000_000), 50_000, 0, PRICE_ONE, k).unwrap();
+        let after = PmmReserves {
+            reserve_in: 1_000_000 - first.result.withdraw,
+            target_in: first.target_out,
+            reserve_out: 1_000_000 + 50_000,
+            target_out: first.target_in,
+        };
+        let back = swap_amount_out(after, first.result.withdraw, 0, PRICE_ONE, k).unwrap();
+        assert!(back.result.withdraw <= 50_000);
+    }
+
+    #[test]
+    fn targets_follow_the_oracle() {
+        // Output side short after earlier trades; re-anchoring grows its target
+        let reserves = PmmReserves {
+            reserve_in: 1_100_000,
+            target_in: 1_000_000,
+            reserve_out: 900_000,
+            target_out: 1_000_000,
+        };
+        let adjusted = adjust_targets(reserves, PRICE_ONE, WAD / 2).unwrap();
+        assert_eq!(adjusted.target_in, 1_000_000);
+        assert!(adjusted.target_out > 900_000 && adjusted.target_out <= 1_000_000);
+
+        // If the price doubles, the input surplus is worth more so the target grows
+        let doubled = adjust_targets(reserves, 2 * PRICE_ONE, WAD / 2).unwrap();
+        assert!(doubled.target_out > adjusted.target_out);
+    }
+
+    #[test]
+    fn cannot_drain_a_side() {
+        let res = swap_amount_out(balanced(1_000), 5_000, 0, PRICE_ONE, 0);
+        assert_eq!(res.unwrap_err(), AmmError::InsufficientBalance.into());
+    }
+
+    #[test]
+    fn inverse_price_rounds_down() {
+        assert_eq!(inverse_price_wad(2 * WAD).unwrap(), WAD / 2);
+        assert_eq!(inverse_price_wad(3 * WAD).unwrap(), WAD / 3);
+    }
+}
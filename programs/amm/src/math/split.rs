@@ -0,0 +1,113 @@
+// Order splitting across several pools of the same pair
+// The input is handed out in equal steps, each going to whichever pool pays the most
+// for it. That leaves every pool used at roughly the same marginal price.
+
+use anchor_lang::prelude::*;
+
+use crate::errors::AmmError;
+
+// Number of steps the input is divided into
+pub const SPLIT_STEPS: u64 = 16;
+
+// Split `amount` across `pools` pools, where `quote(pool, amount_in)` returns the
+// output that pool would pay for `amount_in` (or an error if it can't take it)
+pub fn split_amount<F>(amount: u64, pools: usize, mut quote: F) -> Result<Vec<u64>>
+where
+    F: FnMut(usize, u64) -> Result<u64>,
+{
+    require!(amount != 0, AmmError::InvalidAmount);
+    require!(pools != 0, AmmError::InvalidAccountList);
+
+    let steps = SPLIT_STEPS.min(amount);
+    let mut amounts = vec![0u64; pools];
+    let mut outputs = vec![0u64; pools];
+
+    for step in 0..steps {
+        // Spread any remainder over the first steps
+        let chunk = amount / steps + u64::from(step < amount % steps);
+
+        // (pool, extra output for this chunk, total output)
+        let mut best: Option<(usize, u64, u64)> = None;
+        for pool in 0..pools {
+            let Ok(output) = quote(pool, amounts[pool] + chunk) else {
+                continue;
+            };
+            let gain = output.saturating_sub(outputs[pool]);
+            let better = match best {
+                Some((_, best_gain, _)) => gain > best_gain,
+                None => true,
+            };
+            if better {
+                best = Some((pool, gain, output));
+            }
+        }
+
+        let (pool, _, output) = best.ok_or(AmmError::InsufficientBalance)?;
+        amounts[pool] += chunk;
+        outputs[pool] = output;
+    }
+
+    Ok(amounts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::swap_amount_out;
+
+    fn constant_product(pools: &[(u64, u64, u16)]) -> impl FnMut(usize, u64) -> Result<u64> + '_ {
+        |pool, amount| {
+            let (x, y, fee) = pools[pool];
+            Ok(swap_amount_out(x, y, amount, fee)?.withdraw)
+        }
+    }
+
+    #[test]
+    fn identical_pools_share_evenly() {
+        let pools = [(1_000_000, 1_000_000, 30), (1_000_000, 1_000_000, 30)];
+        let amounts = split_amount(160_000, 2, constant_product(&pools)).unwrap();
+        assert_eq!(amounts, vec![80_000, 80_000]);
+    }
+
+    #[test]
+    fn deeper_pools_take_more() {
+        let pools = [(1_000_000, 1_000_000, 30), (3_000_000, 3_000_000, 30)];
+        let amounts = split_amount(400_000, 2, constant_product(&pools)).unwrap();
+        assert_eq!(amounts.iter().sum::<u64>(), 400_000);
+        // Marginal prices are equal when the input is split in proportion to depth
+        assert_eq!(amounts, vec![100_000, 300_000]);
+    }
+
+    #[test]
+    fn split_beats_any_single_pool() {
+        let pools = [(1_000_000, 1_000_000, 30), (2_000_000, 2_000_000, 5), (500_000, 500_000, 100)];
+        let amount = 300_000;
+        let amounts = split_amount(amount, 3, constant_product(&pools)).unwrap();
+        let split_out: u64 = amounts
+            .iter()
+            .zip(pools.iter())
+            .filter(|(a, _)| **a != 0)
+            .map(|(a, (x, y, fee))| swap_amount_out(*x, *y, *a, *fee).unwrap().withdraw)
+            .sum();
+        for (x, y, fee) in pools {
+            assert!(split_out >= swap_amount_out(x, y, amount, fee).unwrap().withdraw);
+        }
+    }
+
+    #[test]
+    fn small_amounts_are_split_in_single_units() {
+        let pools = [(1_000, 1_000, 0), (1_000, 1_000, 0)];
+        let amounts = split_amount(3, 2, constant_product(&pools)).unwrap();
+        assert_eq!(amounts.iter().sum::<u64>(), 3);
+    }
+
+    #[test]
+    fn skips_pools_that_cannot_quote() {
+        let pools = [(0, 1_000, 0), (1_000, 1_000, 0)];
+        let amounts = split_amount(100, 2, constant_product(&pools)).unwrap();
+        assert_eq!(amounts, vec![0, 100]);
+
+        let empty = [(0, 1_000, 0)];
+        assert_eq!(split_amount(100, 1, constant_product(&empty)).unwrap_err(), AmmError::InsufficientBalance.into());
+    }
+}
@@ -0,0 +1,220 @@
+// Weighted pool math (Balancer-style), used by liquidity bootstrapping pools
+// The pool keeps x^wx * y^wy constant, so a swap pays out
+// out = reserve_out * (1 - (reserve_in / (reserve_in + amount_in)) ^ (weight_in / weight_out))
+
+use anchor_lang::prelude::*;
+
+use crate::errors::AmmError;
+
+use super::{fee_amount, mul_div_ceil_u128, to_u64, SwapResult, FEE_DENOMINATOR, WAD};
+
+// Weights are in basis points of the whole pool, bounded to keep exponents reasonable
+pub const MIN_WEIGHT: u16 = 100;
+pub const MAX_WEIGHT: u16 = 9_900;
+
+// Longest weight schedule a pool can run, in seconds (one year)
+pub const MAX_SCHEDULE_DURATION: i64 = 365 * 24 * 60 * 60;
+
+// ln(2) in WAD
+const LN_2: u128 = 693_147_180_559_945_309;
+
+// Relative error allowed for powers, added to every result so rounding favors the pool
+const MAX_POW_RELATIVE_ERROR: u128 = 10_000; // 1e-14 in WAD
+
+// Linearly interpolate the weight of X at `now` between the start and end of the schedule
+pub fn weight_at(start_weight: u16, end_weight: u16, start_time: i64, end_time: i64, now: i64) -> Result<u16> {
+    if now <= start_time {
+        return Ok(start_weight);
+    }
+    if now >= end_time {
+        return Ok(end_weight);
+    }
+
+    // start_time < now < end_time here, so both differences are positive if they don't overflow
+    let elapsed = now.checked_sub(start_time).ok_or(AmmError::Overflow)? as u128;
+    let duration = end_time.checked_sub(start_time).ok_or(AmmError::Overflow)? as u128;
+
+    // elapsed < duration, so the change is smaller than the difference between the weights
+    let weight = match end_weight >= start_weight {
+        true => start_weight as u128 + (end_weight - start_weight) as u128 * elapsed / duration,
+        false => start_weight as u128 - (start_weight - end_weight) as u128 * elapsed / duration,
+    };
+
+    Ok(weight as u16)
+}
+
+// Calculate the output of a weighted pool swap, rounded down
+pub fn swap_amount_out(
+    reserve_in: u64,
+    weight_in: u16,
+    reserve_out: u64,
+    weight_out: u16,
+    amount_in: u64,
+    fee: u16,
+) -> Result<SwapResult> {
+    require!(reserve_in != 0 && reserve_out != 0, AmmError::ZeroBalance);
+    require!(amount_in != 0, AmmError::InvalidAmount);
+    require!(weight_in != 0 && weight_out != 0, AmmError::InvalidAmount);
+    require!((weight_in as u64 + weight_out as u64) == FEE_DENOMINATOR, AmmError::InvalidAmount);
+
+    let fee_paid = fee_amount(amount_in, fee)?;
+    let amount_in_after_fee = amount_in.checked_sub(fee_paid).ok_or(AmmError::Underflow)?;
+
+    // Base rounded up and exponent rounded down both shrink the output
+    let new_reserve_in = reserve_in as u128 + amount_in_after_fee as u128;
+    let base = mul_div_ceil_u128(reserve_in as u128, WAD, new_reserve_in)?;
+    let exponent = weight_in as u128 * WAD / weight_out as u128;
+
+    let power = pow_wad_up(base, exponent)?;
+    let withdraw = reserve_out as u128 * (WAD - power) / WAD;
+
+    Ok(SwapResult {
+        deposit: amount_in,
+        withdraw: to_u64(withdraw)?,
+        fee: fee_paid,
+    })
+}
+
+// Calculate base ^ exponent for 0 < base <= 1 (WAD), rounded up
+pub fn pow_wad_up(base: u128, exponent: u128) -> Result<u128> {
+    require!(base != 0 && base <= WAD, AmmError::InvalidAmount);
+
+    let power = match (base == WAD, exponent == WAD) {
+        (true, _) => return Ok(WAD),
+        (false, true) => base,
+        (false, false) => {
+            let log = neg_ln_wad(base)?;
+            let product = log.checked_mul(exponent).ok_or(AmmError::Overflow)? / WAD;
+            exp_neg_wad(product)?
+        }
+    };
+
+    let margin = power * MAX_POW_RELATIVE_ERROR / WAD + 1;
+    Ok((power + margin).min(WAD))
+}
+
+// Calculate -ln(x) for 0 < x <= 1 (WAD)
+fn neg_ln_wad(x: u128) -> Result<u128> {
+    require!(x != 0 && x <= WAD, AmmError::InvalidAmount);
+
+    // -ln(x) = ln(1 / x), and 1 / x = 2^n * m with 1 <= m < 2
+    let mut y = WAD.checked_mul(WAD).ok_or(AmmError::Overflow)? / x;
+    let mut n = 0u128;
+    while y >= 2 * WAD {
+        y /= 2;
+        n += 1;
+    }
+
+    // ln(m) = 2 * atanh(z) = 2 * (z + z^3 / 3 + z^5 / 5 + ...), with z = (m - 1) / (m + 1) < 1/3
+    let z = (y - WAD) * WAD / (y + WAD);
+    let z_squared = z * z / WAD;
+    let mut term = z;
+    let mut sum = 0u128;
+    let mut k = 1u128;
+    while term != 0 {
+        sum += term / k;
+        term = term * z_squared / WAD;
+        k += 2;
+    }
+
+    Ok(n * LN_2 + 2 * sum)
+}
+
+// Calculate e^(-t) for t >= 0 (WAD)
+fn exp_neg_wad(t: u128) -> Result<u128> {
+    // e^(-t) = 2^(-n) * e^(-r), with t = n * ln(2) + r and 0 <= r < ln(2)
+    let n = t / LN_2;
+    if n >= 128 {
+        return Ok(0);
+    }
+    let r = t - n * LN_2;
+
+    // e^r = 1 + r + r^2 / 2! + ...
+    let mut term = WAD;
+    let mut sum = WAD;
+    let mut k = 1u128;
+    while term != 0 {
+        term = term * r / WAD / k;
+        sum += term;
+        k += 1;
+    }
+
+    Ok((WAD * WAD / sum) >> n)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: u128, expected: u128, tolerance: u128) {
+        let diff = actual.abs_diff(expected);
+        assert!(diff <= tolerance, "{actual} differs from {expected} by {diff}");
+    }
+
+    #[test]
+    fn weight_interpolates_over_the_window() {
+        assert_eq!(weight_at(9_500, 5_000, 100, 200, 50).unwrap(), 9_500);
+        assert_eq!(weight_at(9_500, 5_000, 100, 200, 100).unwrap(), 9_500);
+        assert_eq!(weight_at(9_500, 5_000, 100, 200, 150).unwrap(), 7_250);
+        assert_eq!(weight_at(9_500, 5_000, 100, 200, 200).unwrap(), 5_000);
+        assert_eq!(weight_at(9_500, 5_000, 100, 200, 1_000).unwrap(), 5_000);
+        assert_eq!(weight_at(2_000, 8_000, 0, 3, 1).unwrap(), 4_000);
+        // A fixed schedule behaves like a static weighted pool
+        assert_eq!(weight_at(8_000, 8_000, 100, 100, 100).unwrap(), 8_000);
+    }
+
+    #[test]
+    fn weight_handles_extreme_times() {
+        assert!(weight_at(9_500, 5_000, i64::MIN, i64::MAX, 0).is_err());
+        assert_eq!(weight_at(9_500, 5_000, i64::MIN, i64::MAX, i64::MIN).unwrap(), 9_500);
+        assert_eq!(weight_at(9_500, 5_000, i64::MIN, i64::MAX, i64::MAX).unwrap(), 5_000);
+        assert_eq!(weight_at(2_000, 8_000, i64::MAX - 2, i64::MAX, i64::MAX - 1).unwrap(), 5_000);
+    }
+
+    #[test]
+    fn logarithm_and_exponent_are_accurate() {
+        assert_close(neg_ln_wad(WAD / 2).unwrap(), 693_147_180_559_945_309, 10);
+        assert_close(neg_ln_wad(WAD).unwrap(), 0, 0);
+        assert_close(exp_neg_wad(3 * WAD).unwrap(), 49_787_068_367_863_942, 10);
+        assert_close(exp_neg_wad(0).unwrap(), WAD, 0);
+        assert_eq!(exp_neg_wad(200 * WAD).unwrap(), 0);
+    }
+
+    #[test]
+    fn power_rounds_up() {
+        // 0.3 ^ 1.7 = 0.129153486074980267...
+        let power = pow_wad_up(3 * WAD / 10, 17 * WAD / 10).unwrap();
+        assert!(power >= 129_153_486_074_980_267);
+        assert_close(power, 129_153_486_074_980_267, 10_000_000);
+        assert_eq!(pow_wad_up(WAD, 5 * WAD).unwrap(), WAD);
+    }
+
+    #[test]
+    fn equal_weights_match_constant_product() {
+        let weighted = swap_amount_out(1_000_000_000, 5_000, 1_000_000_000, 5_000, 100_000_000, 30).unwrap();
+        let constant_product = super::super::swap_amount_out(1_000_000_000, 1_000_000_000, 100_000_000, 30).unwrap();
+        assert_eq!(weighted.fee, constant_product.fee);
+        assert!(weighted.withdraw <= constant_product.withdraw);
+        assert_close(weighted.withdraw as u128, constant_product.withdraw as u128, 1);
+    }
+
+    #[test]
+    fn uneven_weights_match_reference_values() {
+        // 1e9 * (1 - (1 / 1.1) ^ 4) = 316_986_544.63
+        let res = swap_amount_out(1_000_000_000, 8_000, 1_000_000_000, 2_000, 100_000_000, 0).unwrap();
+        assert!(res.withdraw <= 316_986_544);
+        assert_close(res.withdraw as u128, 316_986_544, 20);
+
+        // 1e9 * (1 - (1 / 1.1) ^ 0.25) = 23_545_910.32
+        let res = swap_amount_out(1_000_000_000, 2_000, 1_000_000_000, 8_000, 100_000_000, 0).unwrap();
+        assert!(res.withdraw <= 23_545_910);
+        assert_close(res.withdraw as u128, 23_545_910, 20);
+    }
+
+    #[test]
+    fn rejects_invalid_weights_and_empty_pools() {
+        assert_eq!(swap_amount_out(1_000, 6_000, 1_000, 5_000, 10, 0).unwrap_err(), AmmError::InvalidAmount.into());
+        assert_eq!(swap_amount_out(1_000, 0, 1_000, 10_000, 10, 0).unwrap_err(), AmmError::InvalidAmount.into());
+        assert_eq!(swap_amount_out(0, 5_000, 1_000, 5_000, 10, 0).unwrap_err(), AmmError::ZeroBalance.into());
+    }
+}
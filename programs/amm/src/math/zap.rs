@@ -0,0 +1,132 @@
+// Single-sided deposits ("zaps")
+// A zap in sells part of a one-token deposit to the pool and adds the rest plus the output
+// as balanced liquidity. The part sold is sized so the two sides end up as close as possible
+// to the pool's post-swap ratio, which leaves the depositor the most LP and the least dust
+
+use anchor_lang::prelude::*;
+
+use crate::errors::AmmError;
+
+use super::{mul_div_floor_u128, to_u64, U256};
+
+// Result of sizing a zap in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ZapIn {
+    pub swap_in: u64, // Part of the deposit sold to the pool (fee included)
+    pub swap_out: u64, // Pool output for swap_in
+    pub lp: u64, // LP minted for the balanced remainder (rounded down)
+}
+
+// Size a zap of `amount` into a pool holding `reserve_in` / `reserve_out` with `supply` LP,
+// where `quote(amount_in)` returns the pool's output for selling `amount_in`
+pub fn zap_in<F>(amount: u64, reserve_in: u64, reserve_out: u64, supply: u64, mut quote: F) -> Result<ZapIn>
+where
+    F: FnMut(u64) -> Result<u64>,
+{
+    require!(supply != 0, AmmError::NoLiquidityInPool);
+    require!(amount != 0, AmmError::InvalidAmount);
+
+    // Find the largest sale that still leaves at least as much input as the output can pair with:
+    // (amount - s) / (reserve_in + s) >= out(s) / (reserve_out - out(s))
+    // The left side falls and the right side rises as s grows, so this is a binary search
+    let (mut low, mut high) = (0u64, amount);
+    let mut low_out = 0u64;
+
+    while low < high {
+        let mid = high - (high - low) / 2;
+        let out = quote(mid)?;
+        require!(out < reserve_out, AmmError::Underflow);
+
+        let left = U256::from(amount - mid) * U256::from(reserve_out - out);
+        let right = U256::from(out) * (U256::from(reserve_in) + U256::from(mid));
+        match left >= right {
+            true => {
+                low = mid;
+                low_out = out;
+            }
+            false => high = mid - 1,
+        }
+    }
+
+    // The best sale is either side of the boundary: at `low` the output side limits the LP,
+    // one unit more and the input side does
+    let mut best = ZapIn {
+        swap_in: low,
+        swap_out: low_out,
+        lp: zap_liquidity(amount, low, low_out, reserve_in, reserve_out, supply)?,
+    };
+    if low < amount {
+        let out = quote(low + 1)?;
+        require!(out < reserve_out, AmmError::Underflow);
+
+        let lp = zap_liquidity(amount, low + 1, out, reserve_in, reserve_out, supply)?;
+        if lp > best.lp {
+            best = ZapIn { swap_in: low + 1, swap_out: out, lp };
+        }
+    }
+
+    Ok(best)
+}
+
+// LP minted for depositing what's left of `amount` after selling `swap_in` for `swap_out`,
+// against the reserves after that sale (rounded down)
+fn zap_liquidity(amount: u64, swap_in: u64, swap_out: u64, reserve_in: u64, reserve_out: u64, supply: u64) -> Result<u64> {
+    let reserve_in = reserve_in as u128 + swap_in as u128;
+    let reserve_out = (reserve_out - swap_out) as u128;
+
+    let lp_in = mul_div_floor_u128((amount - swap_in) as u128, supply as u128, reserve_in)?;
+    let lp_out = mul_div_floor_u128(swap_out as u128, supply as u128, reserve_out)?;
+    to_u64(lp_in.min(lp_out))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::{deposit_amounts, swap_amount_out};
+
+    fn constant_product(reserve_in: u64, reserve_out: u64, fee: u16) -> impl FnMut(u64) -> Result<u64> {
+        move |amount| match amount {
+            0 => Ok(0),
+            _ => Ok(swap_amount_out(reserve_in, reserve_out, amount, fee)?.withdraw),
+        }
+    }
+
+    #[test]
+    fn zap_sells_about_half_into_a_deep_pool() {
+        let res = zap_in(10_000, 1_000_000_000, 1_000_000_000, 1_000_000_000, constant_product(1_000_000_000, 1_000_000_000, 30)).unwrap();
+        assert!(res.swap_in > 4_990 && res.swap_in < 5_020);
+        assert!(res.lp > 4_950 && res.lp < 5_000);
+    }
+
+    #[test]
+    fn zap_beats_every_other_sale_size() {
+        let (x, y, supply) = (2_000_000u64, 500_000u64, 1_000_000u64);
+        let res = zap_in(300_000, x, y, supply, constant_product(x, y, 25)).unwrap();
+
+        let mut quote = constant_product(x, y, 25);
+        for swap_in in (0..=300_000).step_by(997) {
+            let out = quote(swap_in).unwrap();
+            assert!(zap_liquidity(300_000, swap_in, out, x, y, supply).unwrap() <= res.lp);
+        }
+    }
+
+    #[test]
+    fn zap_never_needs_more_than_it_has() {
+        for (amount, x, y, supply) in [(1u64, 1_000u64, 1_000u64, 1_000u64), (77_777, 123_456, 654_321, 300_000), (5_000_000, 1_000_000, 1_000_000, 999_999)] {
+            let res = zap_in(amount, x, y, supply, constant_product(x, y, 30)).unwrap();
+            if res.lp == 0 {
+                continue;
+            }
+
+            let need = deposit_amounts(x + res.swap_in, y - res.swap_out, supply, res.lp).unwrap();
+            assert!(need.x <= amount - res.swap_in);
+            assert!(need.y <= res.swap_out);
+        }
+    }
+
+    #[test]
+    fn zap_requires_existing_liquidity() {
+        assert_eq!(zap_in(1_000, 0, 0, 0, constant_product(1, 1, 0)).unwrap_err(), AmmError::NoLiquidityInPool.into());
+        assert_eq!(zap_in(0, 1_000, 1_000, 1_000, constant_product(1_000, 1_000, 0)).unwrap_err(), AmmError::InvalidAmount.into());
+    }
+}
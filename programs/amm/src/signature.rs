@@ -0,0 +1,142 @@
+// Checks for signatures verified by the native ed25519 program
+// The program can't verify signatures itself, so the transaction includes an ed25519 program
+// instruction right before ours, and we read it back through the instructions sysvar to confirm
+// it verified the expected signer over the expected message
+
+use anchor_lang::{
+    prelude::*,
+    solana_program::{
+        ed25519_program,
+        instruction::Instruction,
+        sysvar::instructions::{load_current_index_checked, load_instruction_at_checked},
+    },
+};
+
+use crate::errors::AmmError;
+
+// Layout of the ed25519 program's instruction data: a signature count and a padding byte,
+// then one 14-byte offsets entry per signature
+const OFFSETS_START: usize = 2;
+const OFFSETS_SIZE: usize = 14;
+const PUBKEY_SIZE: usize = 32;
+const SIGNATURE_SIZE: usize = 64;
+
+// Offsets entries pointing at this value read from the ed25519 instruction's own data
+const CURRENT_INSTRUCTION: u16 = u16::MAX;
+
+// Check that the instruction right before the current one verified `signer`'s signature over `message`
+pub fn verify_previous_ed25519(instructions: &AccountInfo, signer: &Pubkey, message: &[u8]) -> Result<()> {
+    let current = load_current_index_checked(instructions)?;
+    require!(current > 0, AmmError::InvalidSignature);
+
+    let ix = load_instruction_at_checked(current as usize - 1, instructions)?;
+    verify_ed25519_ix(&ix, signer, message)
+}
+
+// Check that an ed25519 program instruction verifies exactly one signature, by `signer` over `message`
+pub fn verify_ed25519_ix(ix: &Instruction, signer: &Pubkey, message: &[u8]) -> Result<()> {
+    require_keys_eq!(ix.program_id, ed25519_program::ID, AmmError::InvalidSignature);
+
+    let data = &ix.data;
+    require!(data.len() >= OFFSETS_START + OFFSETS_SIZE, AmmError::InvalidSignature);
+    require!(data[0] == 1, AmmError::InvalidSignature);
+
+    let read = |at: usize| u16::from_le_bytes([data[OFFSETS_START + at], data[OFFSETS_START + at + 1]]);
+    let signature_offset = read(0) as usize;
+    let signature_ix = read(2);
+    let pubkey_offset = read(4) as usize;
+    let pubkey_ix = read(6);
+    let message_offset = read(8) as usize;
+    let message_size = read(10) as usize;
+    let message_ix = read(12);
+
+    // Everything must come from the ed25519 instruction itself, so what we read is what was verified
+    require!(
+        signature_ix == CURRENT_INSTRUCTION && pubkey_ix == CURRENT_INSTRUCTION && message_ix == CURRENT_INSTRUCTION,
+        AmmError::InvalidSignature
+    );
+
+    data.get(signature_offset..signature_offset + SIGNATURE_SIZE).ok_or(AmmError::InvalidSignature)?;
+    let pubkey = data.get(pubkey_offset..pubkey_offset + PUBKEY_SIZE).ok_or(AmmError::InvalidSignature)?;
+    let signed = data.get(message_offset..message_offset + message_size).ok_or(AmmError::InvalidSignature)?;
+
+    require!(pubkey == signer.as_ref(), AmmError::InvalidSignature);
+    require!(signed == message, AmmError::InvalidSignature);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Build ed25519 instruction data the way the native program's client does
+    fn ed25519_data(signer: &Pubkey, message: &[u8], ix_index: u16) -> Vec<u8> {
+        let pubkey_offset = OFFSETS_START + OFFSETS_SIZE;
+        let signature_offset = pubkey_offset + PUBKEY_SIZE;
+        let message_offset = signature_offset + SIGNATURE_SIZE;
+
+        let mut data = vec![1u8, 0];
+        for value in [
+            signature_offset as u16,
+            ix_index,
+            pubkey_offset as u16,
+            ix_index,
+            message_offset as u16,
+            message.len() as u16,
+            ix_index,
+        ] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend_from_slice(signer.as_ref());
+        data.extend_from_slice(&[7u8; SIGNATURE_SIZE]);
+        data.extend_from_slice(message);
+        data
+    }
+
+    fn ed25519_ix(data: Vec<u8>) -> Instruction {
+        Instruction {
+            program_id: ed25519_program::ID,
+            accounts: vec![],
+            data,
+        }
+    }
+
+    #[test]
+    fn accepts_matching_signer_and_message() {
+        let signer = Pubkey::new_unique();
+        let ix = ed25519_ix(ed25519_data(&signer, b"swap", CURRENT_INSTRUCTION));
+        assert!(verify_ed25519_ix(&ix, &signer, b"swap").is_ok());
+    }
+
+    #[test]
+    fn rejects_other_signer_or_message() {
+        let signer = Pubkey::new_unique();
+        let ix = ed25519_ix(ed25519_data(&signer, b"swap", CURRENT_INSTRUCTION));
+        assert_eq!(verify_ed25519_ix(&ix, &Pubkey::new_unique(), b"swap").unwrap_err(), AmmError::InvalidSignature.into());
+        assert_eq!(verify_ed25519_ix(&ix, &signer, b"swap more").unwrap_err(), AmmError::InvalidSignature.into());
+    }
+
+    #[test]
+    fn rejects_data_read_from_other_instructions() {
+        let signer = Pubkey::new_unique();
+        let ix = ed25519_ix(ed25519_data(&signer, b"swap", 0));
+        assert_eq!(verify_ed25519_ix(&ix, &signer, b"swap").unwrap_err(), AmmError::InvalidSignature.into());
+    }
+
+    #[test]
+    fn rejects_other_programs_and_malformed_data() {
+        let signer = Pubkey::new_unique();
+        let mut ix = ed25519_ix(ed25519_data(&signer, b"swap", CURRENT_INSTRUCTION));
+        ix.program_id = Pubkey::new_unique();
+        assert_eq!(verify_ed25519_ix(&ix, &signer, b"swap").unwrap_err(), AmmError::InvalidSignature.into());
+
+        let mut data = ed25519_data(&signer, b"swap", CURRENT_INSTRUCTION);
+        data[0] = 2;
+        assert_eq!(verify_ed25519_ix(&ed25519_ix(data), &signer, b"swap").unwrap_err(), AmmError::InvalidSignature.into());
+
+        let mut data = ed25519_data(&signer, b"swap", CURRENT_INSTRUCTION);
+        data.truncate(data.len() - 1);
+        assert_eq!(verify_ed25519_ix(&ed25519_ix(data), &signer, b"swap").unwrap_err(), AmmError::InvalidSignature.into());
+    }
+}
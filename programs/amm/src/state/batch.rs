@@ -0,0 +1,117 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::AmmError;
+
+// Largest number of intents one batch can collect
+pub const MAX_BATCH_INTENTS: usize = 16;
+
+// One swap intent submitted to a batch
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct BatchIntent {
+    pub owner: Pubkey, // Who submitted the intent and receives the output
+    pub is_x: bool, // Whether the intent sells token X
+    pub amount: u64, // Amount of the input token escrowed
+    pub min_amount_out: u64, // Least output accepted at the clearing price
+    pub filled: bool, // Whether the intent cleared (otherwise it's refunded)
+    pub claimed: bool, // Whether the output or refund has been claimed
+}
+
+// 32 bytes for owner + 1 for side + 8 for amount + 8 for min amount out + 1 for filled + 1 for claimed
+impl Space for BatchIntent {
+    const INIT_SPACE: usize = 32 + 1 + 8 + 8 + 1 + 1;
+}
+
+// This struct stores a batch auction collecting swap intents for a pool over a window of slots
+// Inputs are escrowed in the batch's associated token accounts, and once the window ends the whole
+// batch clears at one uniform price, so ordering inside the window doesn't matter
+#[account]
+pub struct Batch {
+    pub config: Pubkey, // Pool the batch trades against
+    pub id: u64, // Sequential id of the batch within the pool
+    pub end_slot: u64, // First slot at which the batch stops collecting intents and can be settled
+    pub settled: bool, // Whether the batch has been cleared
+    pub total_x: u64, // Total X sold by filled intents
+    pub total_y: u64, // Total Y sold by filled intents
+    pub x_out: u64, // Total X shared between filled Y sellers
+    pub y_out: u64, // Total Y shared between filled X sellers
+    pub intents: Vec<BatchIntent>, // Intents in the batch
+    pub batch_bump: u8, // PDA bump for the batch account
+}
+
+// 8 bytes for discriminator + 32 for config + 8 for id + 8 for end slot + 1 for settled
+// + 8 for each total + 8 for each output + 4 + MAX_BATCH_INTENTS intents + 1 for bump
+impl Space for Batch {
+    const INIT_SPACE: usize = 8 + 32 + 8 + 8 + 1 + 8 + 8 + 8 + 8 + (4 + MAX_BATCH_INTENTS * BatchIntent::INIT_SPACE) + 1;
+}
+
+impl Batch {
+    // Add an intent to the batch, allowing one per owner so a single user can't fill it
+    pub fn add_intent(&mut self, intent: BatchIntent) -> Result<()> {
+        require!(self.intents.len() < MAX_BATCH_INTENTS, AmmError::BatchFull);
+        require!(self.intents.iter().all(|other| other.owner != intent.owner), AmmError::DuplicateIntent);
+
+        self.intents.push(intent);
+
+        Ok(())
+    }
+
+    // Whether every intent has been paid out, leaving only rounding dust in the escrows
+    pub fn all_claimed(&self) -> bool {
+        self.intents.iter().all(|intent| intent.claimed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn batch() -> Batch {
+        Batch {
+            config: Pubkey::new_unique(),
+            id: 0,
+            end_slot: 100,
+            settled: false,
+            total_x: 0,
+            total_y: 0,
+            x_out: 0,
+            y_out: 0,
+            intents: Vec::new(),
+            batch_bump: 255,
+        }
+    }
+
+    fn intent(owner: Pubkey) -> BatchIntent {
+        BatchIntent { owner, is_x: true, amount: 1_000, min_amount_out: 0, filled: false, claimed: false }
+    }
+
+    #[test]
+    fn one_intent_per_owner() {
+        let mut batch = batch();
+        let owner = Pubkey::new_unique();
+        batch.add_intent(intent(owner)).unwrap();
+        assert!(batch.add_intent(intent(owner)).is_err());
+        batch.add_intent(intent(Pubkey::new_unique())).unwrap();
+        assert_eq!(batch.intents.len(), 2);
+    }
+
+    #[test]
+    fn batch_holds_at_most_max_intents() {
+        let mut batch = batch();
+        for _ in 0..MAX_BATCH_INTENTS {
+            batch.add_intent(intent(Pubkey::new_unique())).unwrap();
+        }
+        assert!(batch.add_intent(intent(Pubkey::new_unique())).is_err());
+    }
+
+    #[test]
+    fn all_claimed_once_every_intent_is_paid() {
+        let mut batch = batch();
+        batch.add_intent(intent(Pubkey::new_unique())).unwrap();
+        batch.add_intent(intent(Pubkey::new_unique())).unwrap();
+        assert!(!batch.all_claimed());
+        batch.intents[0].claimed = true;
+        assert!(!batch.all_claimed());
+        batch.intents[1].claimed = true;
+        assert!(batch.all_claimed());
+    }
+}
@@ -1,4 +1,19 @@
 use anchor_lang::prelude::*;
+use anchor_spl::{associated_token::get_associated_token_address, token::TokenAccount};
+
+use crate::{errors::AmmError, math::{self, pmm::PmmReserves, SwapResult}, state::{LpPosition, PriceFeed}};
+
+// Largest number of market makers a pool can allow to fill RFQ quotes
+pub const MAX_MARKET_MAKERS: usize = 8;
+
+// The pricing curve used by a pool
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CurveType {
+    ConstantProduct, // x * y = k, priced only from the pool's own reserves
+    Oracle, // Proactive market maker anchored to an external price feed
+    Weighted, // x^wx * y^wy = k with weights that can shift over time (liquidity bootstrapping)
+    BondingCurve, // Launch selling X for Y on a virtual constant product until the raise target is hit
+}
 
 // This struct stores all the configuration and state for an AMM pool
 // It's stored on-chain and contains all the important pool information
@@ -12,10 +27,645 @@ pub struct Config {
     pub locked: bool, // Whether the pool is locked (prevents trading)
     pub config_bump: u8, // PDA bump for the config account
     pub lp_bump: u8, // PDA bump for the LP token mint
+    pub curve: CurveType, // Which pricing curve the pool uses
+    pub oracle: Option<Pubkey>, // Price feed for oracle-anchored pools
+    pub max_price_age: u64, // Oldest oracle price (in seconds) the pool will trade against
+    pub max_confidence: u16, // Widest oracle confidence interval, in basis points of the price
+    pub pmm_k: u16, // PMM slippage in basis points (0 = fixed oracle price, 10_000 = constant product)
+    pub target_x: u64, // PMM equilibrium reserve of token X
+    pub target_y: u64, // PMM equilibrium reserve of token Y
+    pub start_weight_x: u16, // Weight of token X in basis points when the weight schedule starts
+    pub end_weight_x: u16, // Weight of token X in basis points when the weight schedule ends
+    pub start_time: i64, // Unix timestamp when the weight schedule (and sale) starts
+    pub end_time: i64, // Unix timestamp when the weight schedule (and sale) ends
+    pub virtual_y: u64, // Virtual Y reserve that sets the bonding curve's starting price
+    pub raise_target: u64, // Amount of Y raised that graduates a launch into a constant product pool
+    pub flash_fee: u16, // Flash loan fee in basis points, paid to LPs
+    pub flash_loan: u64, // Amount (principal + fee) owed by the flash loan in progress, if any
+    pub flash_is_x: bool, // Whether the flash loan in progress was taken from vault_x
+    pub batch_slots: u64, // Length of each batch auction in slots (0 = batch mode off)
+    pub batch_id: u64, // Id of the batch currently collecting intents
+    pub min_intent_x: u64, // Smallest X intent a batch accepts, so dust can't fill it
+    pub min_intent_y: u64, // Smallest Y intent a batch accepts, so dust can't fill it
+    pub market_makers: Vec<Pubkey>, // Market makers whose signed quotes can fill swaps alongside the curve
+    pub reserve_x: u64, // Token X the pool prices against (tokens sent straight to vault_x aren't counted until synced)
+    pub reserve_y: u64, // Token Y the pool prices against (tokens sent straight to vault_y aren't counted until synced)
+    pub permissionless_sync: bool, // Whether anyone can sync the vaults, not just the authority
+    pub time_locked_lp: u64, // LP tokens currently held in time-locks
+    pub burned_lp: u64, // LP tokens locked forever (burn locks and launch graduation), so never withdrawable
+    pub lp_supply: u64, // LP tokens minted by the pool and not yet burned through it
+    pub fee_growth_x: u128, // Swap and flash loan fees earned per LP token since the pool opened, in X (WAD)
+    pub fee_growth_y: u128, // Swap and flash loan fees earned per LP token since the pool opened, in Y (WAD)
+    pub min_hold: i64, // Seconds LP must be held after a deposit before it can be withdrawn without a penalty (0 = off)
+    pub early_exit_fee: u16, // Penalty in basis points on withdrawals inside the holding period, left in the pool for the other LPs
+    pub max_reserve_x: u64, // Most X deposits can bring the reserves to (0 = no cap)
+    pub max_reserve_y: u64, // Most Y deposits can bring the reserves to (0 = no cap)
+    pub max_lp_supply: u64, // Most LP deposits can bring the supply to (0 = no cap)
+    pub max_wallet_lp: u64, // Most LP one wallet can hold through deposits, as tracked by its position (0 = no cap)
 }
 
 // Define how much space this account needs on-chain
 // 8 bytes for discriminator + 8 for seed + 33 for authority + 32 for each mint + 2 for fee + 1 for locked + 1 for each bump
+// + 1 for curve + 33 for oracle + 8 for max price age + 2 for max confidence + 2 for k + 8 for each target
+// + 2 for each weight + 8 for each timestamp + 8 for virtual Y + 8 for raise target
+// + 2 for flash fee + 8 for flash loan + 1 for flash side + 8 for batch slots + 8 for batch id + 8 for each minimum intent
+// + 4 + 32 for each allowed market maker + 8 for each reserve + 1 for permissionless sync
+// + 8 for time-locked LP + 8 for burned LP + 8 for LP supply + 16 for each fee growth
+// + 8 for min hold + 2 for early exit fee + 8 for each reserve cap + 8 for LP supply cap + 8 for wallet LP cap
 impl Space for Config {
-    const INIT_SPACE: usize = 8 + 8 + (1 + 32) + 32 + 32 + 2 + 1 + 1 + 1;
-}
\ No newline at end of file
+    const INIT_SPACE: usize = 8 + 8 + (1 + 32) + 32 + 32 + 2 + 1 + 1 + 1 + 1 + (1 + 32) + 8 + 2 + 2 + 8 + 8 + 2 + 2 + 8 + 8 + 8 + 8 + 2 + 8 + 1 + 8 + 8 + 8 + 8
+        + (4 + 32 * MAX_MARKET_MAKERS) + 8 + 8 + 1 + 8 + 8 + 8 + 16 + 16 + 8 + 2 + 8 + 8 + 8 + 8;
+}
+
+// The config layout pools were first created with, before curves and pool state were added
+// `migrate_config` grows these accounts to the current layout
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct LegacyConfig {
+    pub seed: u64,
+    pub authority: Option<Pubkey>,
+    pub mint_x: Pubkey,
+    pub mint_y: Pubkey,
+    pub fee: u16,
+    pub locked: bool,
+    pub config_bump: u8,
+    pub lp_bump: u8,
+}
+
+// 8 bytes for discriminator + 8 for seed + 33 for authority + 32 for each mint + 2 for fee + 1 for locked + 1 for each bump
+pub const LEGACY_CONFIG_SPACE: usize = 8 + 8 + (1 + 32) + 32 + 32 + 2 + 1 + 1 + 1;
+
+impl Config {
+    // A new plain constant product pool with nothing in it and every optional feature off
+    pub fn new(seed: u64, authority: Option<Pubkey>, mint_x: Pubkey, mint_y: Pubkey, fee: u16, config_bump: u8, lp_bump: u8) -> Self {
+        Config {
+            seed,
+            authority,
+            mint_x,
+            mint_y,
+            fee,
+            locked: false, // Pool starts unlocked
+            config_bump,
+            lp_bump,
+            curve: CurveType::ConstantProduct, // Plain constant product unless configured otherwise
+            oracle: None,
+            max_price_age: 0,
+            max_confidence: 0,
+            pmm_k: 0,
+            target_x: 0,
+            target_y: 0,
+            start_weight_x: 0,
+            end_weight_x: 0,
+            start_time: 0,
+            end_time: 0,
+            virtual_y: 0,
+            raise_target: 0,
+            flash_fee: fee, // Flash loans pay the trading fee until the authority changes it
+            flash_loan: 0,
+            flash_is_x: false,
+            batch_slots: 0, // Batch mode is off until the authority turns it on
+            batch_id: 0,
+            min_intent_x: 0,
+            min_intent_y: 0,
+            market_makers: Vec::new(), // No RFQ market makers until the authority allows some
+            reserve_x: 0,
+            reserve_y: 0,
+            permissionless_sync: false, // Only the authority can sync until it opens it up
+            time_locked_lp: 0,
+            burned_lp: 0,
+            lp_supply: 0,
+            fee_growth_x: 0,
+            fee_growth_y: 0,
+            min_hold: 0,
+            early_exit_fee: 0,
+            max_reserve_x: 0,
+            max_reserve_y: 0,
+            max_lp_supply: 0,
+            max_wallet_lp: 0,
+        }
+    }
+
+    // Carry a legacy config over to the current layout, with every feature added since then off
+    // Reserves and LP supply start at zero and are filled in from the pool's accounts
+    pub fn from_legacy(legacy: LegacyConfig) -> Self {
+        let mut config = Config::new(
+            legacy.seed,
+            legacy.authority,
+            legacy.mint_x,
+            legacy.mint_y,
+            legacy.fee,
+            legacy.config_bump,
+            legacy.lp_bump,
+        );
+        config.locked = legacy.locked;
+        config
+    }
+
+    // Load one of this pool's vaults passed as a remaining account, checking it's
+    // the config's associated token account for X or Y
+    pub fn vault<'info>(&self, config: Pubkey, is_x: bool, info: &'info AccountInfo<'info>) -> Result<Account<'info, TokenAccount>> {
+        let mint = match is_x {
+            true => self.mint_x,
+            false => self.mint_y,
+        };
+        require_keys_eq!(info.key(), get_associated_token_address(&config, &mint), AmmError::InvalidToken);
+
+        Account::try_from(info)
+    }
+
+    // Read the oracle price of X in Y (raw units, WAD) for oracle-anchored pools
+    // Returns None for pools that don't use an oracle
+    pub fn oracle_price(&self, price_feed: Option<&Account<PriceFeed>>, decimals_x: u8, decimals_y: u8) -> Result<Option<u128>> {
+        if self.curve != CurveType::Oracle {
+            return Ok(None);
+        }
+
+        // The feed must be the one this pool was configured with
+        let price_feed = price_feed.ok_or(AmmError::InvalidOracle)?;
+        require!(self.oracle == Some(price_feed.key()), AmmError::InvalidOracle);
+
+        let now = Clock::get()?.unix_timestamp;
+        let price = price_feed.checked_price(now, self.max_price_age, self.max_confidence)?;
+
+        Ok(Some(math::pmm::oracle_price_wad(price, price_feed.expo, decimals_x, decimals_y)?))
+    }
+
+    // Price a swap on this pool's curve and update any curve state it carries
+    // `price` is the oracle price from `oracle_price`, required for oracle-anchored pools
+    pub fn curve_swap(&mut self, is_x: bool, amount: u64, reserve_x: u64, reserve_y: u64, price: Option<u128>) -> Result<SwapResult> {
+        let (reserve_in, reserve_out) = match is_x {
+            true => (reserve_x, reserve_y),
+            false => (reserve_y, reserve_x),
+        };
+
+        match self.curve {
+            CurveType::ConstantProduct => math::swap_amount_out(reserve_in, reserve_out, amount, self.fee),
+            CurveType::Oracle => {
+                let price = price.ok_or(AmmError::InvalidOracle)?;
+
+                // The oracle quotes X in Y, so selling Y uses the inverse price
+                let (target_in, target_out, price) = match is_x {
+                    true => (self.target_x, self.target_y, price),
+                    false => (self.target_y, self.target_x, math::pmm::inverse_price_wad(price)?),
+                };
+
+                let reserves = PmmReserves {
+                    reserve_in,
+                    target_in,
+                    reserve_out,
+                    target_out,
+                };
+                let k = math::pmm::bps_to_wad(self.pmm_k)?;
+                let res = math::pmm::swap_amount_out(reserves, amount, self.fee, price, k)?;
+
+                // Store the re-anchored targets
+                match is_x {
+                    true => (self.target_x, self.target_y) = (res.target_in, res.target_out),
+                    false => (self.target_y, self.target_x) = (res.target_in, res.target_out),
+                }
+
+                Ok(res.result)
+            }
+            CurveType::Weighted => {
+                let (weight_x, weight_y) = self.weights(Clock::get()?.unix_timestamp)?;
+                let (weight_in, weight_out) = match is_x {
+                    true => (weight_x, weight_y),
+                    false => (weight_y, weight_x),
+                };
+
+                math::weighted::swap_amount_out(reserve_in, weight_in, reserve_out, weight_out, amount, self.fee)
+            }
+            CurveType::BondingCurve => {
+                // Launches only sell X, priced as if the Y side held an extra virtual reserve
+                require!(!is_x, AmmError::InvalidToken);
+
+                let virtual_reserve_in = reserve_in.checked_add(self.virtual_y).ok_or(AmmError::Overflow)?;
+                math::swap_amount_out(virtual_reserve_in, reserve_out, amount, self.fee)
+            }
+        }
+    }
+
+    // Price a swap against the pool's recorded reserves and record it in them,
+    // along with the fee it earned LPs
+    pub fn pool_swap(&mut self, is_x: bool, amount: u64, price: Option<u128>) -> Result<SwapResult> {
+        let res = self.curve_swap(is_x, amount, self.reserve_x, self.reserve_y, price)?;
+        self.record_swap(is_x, res.deposit, res.withdraw)?;
+        self.accrue_fees(is_x, res.fee)?;
+        Ok(res)
+    }
+
+    // Share a fee paid in X (or Y) between the pool's current LP tokens
+    // Nothing is tracked while there's no LP (e.g. a launch before it graduates)
+    pub fn accrue_fees(&mut self, is_x: bool, fee: u64) -> Result<()> {
+        if self.lp_supply == 0 || fee == 0 {
+            return Ok(());
+        }
+
+        let growth = math::mul_div_floor_u128(fee as u128, math::WAD, self.lp_supply as u128)?;
+        match is_x {
+            true => self.fee_growth_x = self.fee_growth_x.checked_add(growth).ok_or(AmmError::Overflow)?,
+            false => self.fee_growth_y = self.fee_growth_y.checked_add(growth).ok_or(AmmError::Overflow)?,
+        }
+
+        Ok(())
+    }
+
+    // With a holding period on, liquidity can only be added or removed through the depositor's position,
+    // which remembers when they last deposited
+    pub fn check_position_required(&self, has_position: bool) -> Result<()> {
+        require!(self.min_hold == 0 || has_position, AmmError::PositionRequired);
+        Ok(())
+    }
+
+    // Penalty left behind out of `out` tokens withdrawn through `position` at `now` (rounded up)
+    // The holding period runs from the position's latest deposit, or from when it was opened, so LP moved
+    // into a fresh position pays it too; after that, withdrawals are free whatever LP they burn
+    pub fn exit_penalty(&self, position: Option<&LpPosition>, out: u64, now: i64) -> Result<u64> {
+        match position {
+            Some(position) if self.min_hold != 0 && now < position.last_deposit.saturating_add(self.min_hold) => {
+                math::fee_amount(out, self.early_exit_fee)
+            }
+            _ => Ok(0),
+        }
+    }
+
+    // Check a deposit that's already been recorded against the pool's caps, given the LP the
+    // depositor's position holds after it (if they passed one)
+    // The wallet cap counts the LP a wallet has deposited and not withdrawn, so it needs the position;
+    // moving LP out of the wallet doesn't make room for more
+    pub fn check_deposit_caps(&self, wallet_lp: Option<u64>) -> Result<()> {
+        require!(self.max_reserve_x == 0 || self.reserve_x <= self.max_reserve_x, AmmError::DepositCapExceeded);
+        require!(self.max_reserve_y == 0 || self.reserve_y <= self.max_reserve_y, AmmError::DepositCapExceeded);
+        require!(self.max_lp_supply == 0 || self.lp_supply <= self.max_lp_supply, AmmError::DepositCapExceeded);
+        if self.max_wallet_lp != 0 {
+            let wallet_lp = wallet_lp.ok_or(AmmError::PositionRequired)?;
+            require!(wallet_lp <= self.max_wallet_lp, AmmError::WalletCapExceeded);
+        }
+        Ok(())
+    }
+
+    // Lend `amount` out of one side's reserves, locking the pool until it's repaid
+    pub fn start_flash_loan(&mut self, is_x: bool, amount: u64) -> Result<()> {
+        // Record what's owed, fee rounded up
+        let fee = math::fee_amount(amount, self.flash_fee)?;
+        self.flash_loan = amount.checked_add(fee).ok_or(AmmError::Overflow)?;
+        self.flash_is_x = is_x;
+
+        // The fee is earned by the LPs as soon as the loan is taken, since the repay must follow
+        self.accrue_fees(is_x, fee)?;
+
+        // Lock the pool until the loan is repaid, so nobody can trade or provide
+        // liquidity against the lent-out reserves
+        self.locked = true;
+
+        match is_x {
+            true => self.record_withdraw(amount, 0),
+            false => self.record_withdraw(0, amount),
+        }
+    }
+
+    // Close the flash loan in progress and unlock the pool, returning the side it was taken from
+    // and what's owed back (principal + fee), which returns to the reserves
+    pub fn repay_flash_loan(&mut self) -> Result<(bool, u64)> {
+        let owed = self.flash_loan;
+        require!(owed != 0, AmmError::NoFlashLoan);
+
+        match self.flash_is_x {
+            true => self.record_deposit(owed, 0)?,
+            false => self.record_deposit(0, owed)?,
+        }
+        self.flash_loan = 0;
+        self.locked = false;
+
+        Ok((self.flash_is_x, owed))
+    }
+
+    // Record LP tokens minted by the pool
+    pub fn record_lp_minted(&mut self, amount: u64) -> Result<()> {
+        self.lp_supply = self.lp_supply.checked_add(amount).ok_or(AmmError::Overflow)?;
+        Ok(())
+    }
+
+    // Record LP tokens burned through the pool
+    pub fn record_lp_burned(&mut self, amount: u64) -> Result<()> {
+        self.lp_supply = self.lp_supply.checked_sub(amount).ok_or(AmmError::Underflow)?;
+        Ok(())
+    }
+
+    // Record a swap in the pool's reserves: `deposit` came into the input side and `withdraw` left the output side
+    pub fn record_swap(&mut self, is_x: bool, deposit: u64, withdraw: u64) -> Result<()> {
+        match is_x {
+            true => self.record_deposit(deposit, 0)?,
+            false => self.record_deposit(0, deposit)?,
+        }
+        match is_x {
+            true => self.record_withdraw(0, withdraw),
+            false => self.record_withdraw(withdraw, 0),
+        }
+    }
+
+    // Record tokens added to the vaults in the pool's reserves
+    pub fn record_deposit(&mut self, x: u64, y: u64) -> Result<()> {
+        self.reserve_x = self.reserve_x.checked_add(x).ok_or(AmmError::Overflow)?;
+        self.reserve_y = self.reserve_y.checked_add(y).ok_or(AmmError::Overflow)?;
+        Ok(())
+    }
+
+    // Record tokens taken out of the vaults in the pool's reserves
+    pub fn record_withdraw(&mut self, x: u64, y: u64) -> Result<()> {
+        self.reserve_x = self.reserve_x.checked_sub(x).ok_or(AmmError::Underflow)?;
+        self.reserve_y = self.reserve_y.checked_sub(y).ok_or(AmmError::Underflow)?;
+        Ok(())
+    }
+
+    // Sync moves what the pool prices against, so unless the pool allows anyone to,
+    // only the authority can call it
+    pub fn check_sync_authority(&self, signer: Pubkey) -> Result<()> {
+        require!(self.permissionless_sync || self.authority == Some(signer), AmmError::InvalidAuthority);
+        Ok(())
+    }
+
+    // Skim sends tokens to a recipient of the caller's choosing, so only the authority can call it
+    pub fn check_skim_authority(&self, signer: Pubkey) -> Result<()> {
+        require!(self.authority == Some(signer), AmmError::InvalidAuthority);
+        Ok(())
+    }
+
+    // Permanently locked LP can never be withdrawn, so the LP left after burning `amount`
+    // must still cover it
+    pub fn check_minimum_liquidity(&self, supply: u64, amount: u64) -> Result<()> {
+        let remaining = supply.checked_sub(amount).ok_or(AmmError::InsufficientBalance)?;
+        require!(remaining >= self.burned_lp, AmmError::InsufficientBalance);
+        Ok(())
+    }
+
+    // In batch mode every trade has to go through a batch auction, so instructions that
+    // trade directly against the pool call this first
+    pub fn check_direct_trading(&self) -> Result<()> {
+        require!(self.batch_slots == 0, AmmError::BatchModeOnly);
+        Ok(())
+    }
+
+    // Whether a launch has raised enough Y to graduate into a constant product pool
+    pub fn should_graduate(&self, reserve_y: u64) -> bool {
+        self.curve == CurveType::BondingCurve && reserve_y >= self.raise_target
+    }
+
+    // Address of the PDA that owns permanently locked LP tokens for this pool
+    // No instruction ever signs for it, so LP sent to its token accounts can't be moved
+    pub fn lock_authority(config: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[b"lock", config.as_ref()], &crate::ID).0
+    }
+
+    // Current weights of X and Y (in basis points) for weighted pools
+    pub fn weights(&self, now: i64) -> Result<(u16, u16)> {
+        // Trading only opens once the schedule starts
+        require!(now >= self.start_time, AmmError::SaleNotStarted);
+
+        let weight_x = math::weighted::weight_at(self.start_weight_x, self.end_weight_x, self.start_time, self.end_time, now)?;
+        Ok((weight_x, math::FEE_DENOMINATOR as u16 - weight_x))
+    }
+
+    // During a liquidity bootstrapping sale only the pool authority may add or remove liquidity,
+    // and launches have no LP until they graduate
+    pub fn check_liquidity_provider(&self, user: Pubkey) -> Result<()> {
+        require!(self.curve != CurveType::BondingCurve, AmmError::LaunchNotGraduated);
+
+        if self.curve == CurveType::Weighted && Clock::get()?.unix_timestamp < self.end_time {
+            require!(self.authority == Some(user), AmmError::InvalidAuthority);
+        }
+
+        Ok(())
+    }
+
+    // Grow the PMM targets in proportion to newly minted LP (or seed them on the first deposit)
+    pub fn deposit_targets(&mut self, x: u64, y: u64, amount: u64, supply: u64) -> Result<()> {
+        if self.curve != CurveType::Oracle {
+            return Ok(());
+        }
+
+        match supply == 0 {
+            true => (self.target_x, self.target_y) = (x, y),
+            false => {
+                let added_x = math::mul_div_floor(self.target_x, amount, supply)?;
+                let added_y = math::mul_div_floor(self.target_y, amount, supply)?;
+                self.target_x = self.target_x.checked_add(added_x).ok_or(AmmError::Overflow)?;
+                self.target_y = self.target_y.checked_add(added_y).ok_or(AmmError::Overflow)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // Shrink the PMM targets in proportion to burned LP
+    pub fn withdraw_targets(&mut self, amount: u64, supply: u64) -> Result<()> {
+        if self.curve != CurveType::Oracle {
+            return Ok(());
+        }
+
+        let removed_x = math::mul_div_ceil(self.target_x, amount, supply)?;
+        let removed_y = math::mul_div_ceil(self.target_y, amount, supply)?;
+        self.target_x = self.target_x.saturating_sub(removed_x);
+        self.target_y = self.target_y.saturating_sub(removed_y);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::state::{lp_position::tests::position, MAX_EARLY_EXIT_FEE};
+
+    // A plain constant product pool with no liquidity, as `initialize` leaves it
+    pub(crate) fn config() -> Config {
+        Config::new(1, Some(Pubkey::new_unique()), Pubkey::new_unique(), Pubkey::new_unique(), 30, 255, 255)
+    }
+
+    #[test]
+    fn records_move_reserves() {
+        let mut config = config();
+        config.record_deposit(1_000, 2_000).unwrap();
+        config.record_withdraw(100, 0).unwrap();
+        config.record_swap(true, 50, 80).unwrap();
+        assert_eq!((config.reserve_x, config.reserve_y), (950, 1_920));
+
+        // Reserves never go negative
+        assert!(config.record_withdraw(951, 0).is_err());
+        assert!(config.record_swap(false, 10, 951).is_err());
+    }
+
+    #[test]
+    fn pool_swap_prices_against_recorded_reserves() {
+        let mut config = config();
+        config.record_deposit(1_000_000, 1_000_000).unwrap();
+
+        let res = config.pool_swap(true, 10_000, None).unwrap();
+        assert_eq!(res, math::swap_amount_out(1_000_000, 1_000_000, 10_000, config.fee).unwrap());
+        assert_eq!((config.reserve_x, config.reserve_y), (1_010_000, 1_000_000 - res.withdraw));
+    }
+
+    #[test]
+    fn only_the_authority_syncs_and_skims_by_default() {
+        let mut config = config();
+        let authority = config.authority.unwrap();
+        let other = Pubkey::new_unique();
+
+        config.check_sync_authority(authority).unwrap();
+        config.check_skim_authority(authority).unwrap();
+        assert!(config.check_sync_authority(other).is_err());
+        assert!(config.check_skim_authority(other).is_err());
+
+        // Opening up sync never opens up skim
+        config.permissionless_sync = true;
+        config.check_sync_authority(other).unwrap();
+        assert!(config.check_skim_authority(other).is_err());
+
+        // A pool without an authority can't be skimmed at all
+        config.authority = None;
+        assert!(config.check_skim_authority(other).is_err());
+    }
+
+    #[test]
+    fn exit_penalty_applies_inside_the_hold_period() {
+        let mut config = config();
+        let held = position(1_000, 100);
+
+        // No holding period, no penalty
+        assert_eq!(config.exit_penalty(Some(&held), 10_000, 100).unwrap(), 0);
+
+        config.min_hold = 50;
+        config.early_exit_fee = 100;
+        assert_eq!(config.exit_penalty(Some(&held), 10_000, 149).unwrap(), 100);
+        assert_eq!(config.exit_penalty(Some(&held), 10_000, 150).unwrap(), 0);
+
+        // Rounded up in the pool's favour
+        assert_eq!(config.exit_penalty(Some(&held), 1, 149).unwrap(), 1);
+
+        // Without a position there's nothing to penalize (deposits and withdrawals then require one)
+        assert_eq!(config.exit_penalty(None, 10_000, 100).unwrap(), 0);
+    }
+
+    #[test]
+    fn hold_period_turned_on_later_only_delays_existing_lps() {
+        let mut config = config();
+        config.record_deposit(1_000_000, 1_000_000).unwrap();
+        config.record_lp_minted(1_000_000).unwrap();
+
+        // The authority turns the hold on after LPs deposited without positions
+        config.min_hold = 50;
+        config.early_exit_fee = MAX_EARLY_EXIT_FEE;
+
+        // An existing LP opens a position at 1,000, which hasn't recorded any of their LP
+        let opened = position(0, 1_000);
+        assert_eq!(config.exit_penalty(Some(&opened), 10_000, 1_049).unwrap(), 1_000);
+
+        // Once the period from opening has passed, their LP comes out in full
+        assert_eq!(config.exit_penalty(Some(&opened), 10_000, 1_050).unwrap(), 0);
+        assert_eq!(config.exit_penalty(Some(&opened), 10_000, 1_000_000).unwrap(), 0);
+    }
+
+    #[test]
+    fn deposit_caps_bound_reserves_supply_and_wallets() {
+        let mut config = config();
+        config.record_deposit(1_000, 2_000).unwrap();
+        config.record_lp_minted(500).unwrap();
+
+        // No caps set, no position needed
+        assert!(config.check_deposit_caps(None).is_ok());
+
+        config.max_reserve_x = 1_000;
+        config.max_reserve_y = 2_000;
+        config.max_lp_supply = 500;
+        assert!(config.check_deposit_caps(None).is_ok());
+        config.record_deposit(1, 0).unwrap();
+        assert!(config.check_deposit_caps(None).is_err());
+        config.max_reserve_x = 0;
+        config.record_lp_minted(1).unwrap();
+        assert!(config.check_deposit_caps(None).is_err());
+        config.max_lp_supply = 0;
+
+        // The wallet cap needs the depositor's position
+        config.max_wallet_lp = 300;
+        assert!(config.check_deposit_caps(None).is_err());
+        assert!(config.check_deposit_caps(Some(300)).is_ok());
+        assert!(config.check_deposit_caps(Some(301)).is_err());
+    }
+
+    #[test]
+    fn burned_lp_can_never_be_withdrawn() {
+        let mut config = config();
+        config.burned_lp = 1_000;
+
+        // Withdrawals must leave at least the burned LP in the supply
+        assert!(config.check_minimum_liquidity(10_000, 9_000).is_ok());
+        assert!(config.check_minimum_liquidity(10_000, 9_001).is_err());
+        assert!(config.check_minimum_liquidity(10_000, 10_001).is_err());
+    }
+
+    #[test]
+    fn fees_accrue_per_lp_share() {
+        let mut config = config();
+
+        // Nothing to share with no LP out
+        config.accrue_fees(true, 1_000).unwrap();
+        assert_eq!(config.fee_growth_x, 0);
+
+        config.record_lp_minted(4_000).unwrap();
+        config.accrue_fees(true, 1_000).unwrap();
+        config.accrue_fees(false, 2_000).unwrap();
+        assert_eq!((config.fee_growth_x, config.fee_growth_y), (math::WAD / 4, math::WAD / 2));
+
+        config.record_lp_burned(3_000).unwrap();
+        assert_eq!(config.lp_supply, 1_000);
+        assert!(config.record_lp_burned(1_001).is_err());
+    }
+
+    #[test]
+    fn flash_loans_return_with_the_fee() {
+        let mut config = config();
+        config.record_deposit(1_000_000, 1_000_000).unwrap();
+        config.record_lp_minted(1_000_000).unwrap();
+        assert!(config.repay_flash_loan().is_err());
+
+        config.start_flash_loan(false, 100_000).unwrap();
+        assert!(config.locked);
+        assert_eq!(config.reserve_y, 900_000);
+        // The LPs earn the fee (30 bps, rounded up) as the loan is taken
+        assert_eq!(config.fee_growth_y, 300 * math::WAD / 1_000_000);
+
+        assert_eq!(config.repay_flash_loan().unwrap(), (false, 100_300));
+        assert!(!config.locked);
+        assert_eq!(config.reserve_y, 1_000_300);
+        assert!(config.repay_flash_loan().is_err());
+    }
+
+    #[test]
+    fn legacy_configs_carry_over() {
+        // Borsh leaves a missing authority short, so the legacy account ends in padding
+        let legacy = LegacyConfig {
+            seed: 7,
+            authority: None,
+            mint_x: Pubkey::new_unique(),
+            mint_y: Pubkey::new_unique(),
+            fee: 25,
+            locked: true,
+            config_bump: 254,
+            lp_bump: 253,
+        };
+        let mut data = vec![0; LEGACY_CONFIG_SPACE - 8];
+        legacy.serialize(&mut data.as_mut_slice()).unwrap();
+        let legacy = LegacyConfig::deserialize(&mut data.as_slice()).unwrap();
+
+        let (mint_x, mint_y) = (legacy.mint_x, legacy.mint_y);
+        let config = Config::from_legacy(legacy);
+        assert_eq!((config.seed, config.authority, config.mint_x, config.mint_y), (7, None, mint_x, mint_y));
+        assert_eq!((config.fee, config.flash_fee, config.locked), (25, 25, true));
+        assert_eq!((config.config_bump, config.lp_bump), (254, 253));
+        assert_eq!(config.curve, CurveType::ConstantProduct);
+
+        // The migrated config fits the space it's grown to
+        let mut data = Vec::new();
+        config.try_serialize(&mut data).unwrap();
+        assert!(data.len() <= Config::INIT_SPACE);
+    }
+}
@@ -0,0 +1,148 @@
+use anchor_lang::prelude::*;
+
+use crate::{errors::AmmError, math};
+
+// Largest number of reward tokens one farm can emit at the same time
+pub const MAX_FARM_REWARDS: usize = 4;
+
+// One reward token emitted by a farm
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct FarmReward {
+    pub mint: Pubkey, // Reward token, held in the farm's associated token account
+    pub emission_rate: u64, // Reward tokens emitted per second, shared between stakers
+    pub reward_per_share: u128, // Rewards emitted per staked LP token so far (WAD)
+    pub remaining: u64, // Funded rewards not emitted yet
+}
+
+// 32 bytes for mint + 8 for emission rate + 16 for reward per share + 8 for remaining
+impl Space for FarmReward {
+    const INIT_SPACE: usize = 32 + 8 + 16 + 8;
+}
+
+// This struct stores an LP farm for a pool
+// Staked LP tokens are held in the farm's associated token account, and every reward token
+// is emitted per second to stakers in proportion to their stake
+#[account]
+pub struct Farm {
+    pub config: Pubkey, // Pool whose LP tokens are staked
+    pub total_staked: u64, // LP tokens staked in the farm
+    pub last_update: i64, // Unix timestamp up to which rewards have been emitted
+    pub rewards: Vec<FarmReward>, // Reward tokens the farm emits
+    pub farm_bump: u8, // PDA bump for the farm account
+}
+
+// 8 bytes for discriminator + 32 for config + 8 for total staked + 8 for last update
+// + 4 + MAX_FARM_REWARDS rewards + 1 for bump
+impl Space for Farm {
+    const INIT_SPACE: usize = 8 + 32 + 8 + 8 + (4 + FarmReward::INIT_SPACE * MAX_FARM_REWARDS) + 1;
+}
+
+impl Farm {
+    // Emit every reward up to `now`
+    // Nothing is emitted while nobody is staked, so those rewards stay funded for later
+    pub fn update(&mut self, now: i64) -> Result<()> {
+        let elapsed = now - self.last_update;
+        self.last_update = now;
+
+        if self.total_staked == 0 {
+            return Ok(());
+        }
+
+        for reward in self.rewards.iter_mut() {
+            let emitted = math::farm::emitted_rewards(reward.emission_rate, elapsed, reward.remaining);
+            reward.reward_per_share = math::farm::reward_per_share(reward.reward_per_share, emitted, self.total_staked)?;
+            reward.remaining -= emitted;
+        }
+
+        Ok(())
+    }
+
+    // Index of the reward paying out `mint`
+    pub fn reward_index(&self, mint: Pubkey) -> Result<usize> {
+        self.rewards.iter().position(|reward| reward.mint == mint).ok_or(error!(AmmError::InvalidToken))
+    }
+}
+
+// This struct stores one owner's stake in a farm
+#[account]
+pub struct StakePosition {
+    pub owner: Pubkey, // Who staked the LP tokens and earns the rewards
+    pub farm: Pubkey, // Farm the LP tokens are staked in
+    pub amount: u64, // LP tokens staked
+    pub reward_per_share_paid: Vec<u128>, // Each reward's reward per share when the position last settled
+    pub owed: Vec<u64>, // Rewards earned but not claimed yet, by reward
+    pub stake_bump: u8, // PDA bump for the position account
+}
+
+// 8 bytes for discriminator + 32 for owner + 32 for farm + 8 for amount
+// + 4 + 16 per reward for reward per share paid + 4 + 8 per reward for owed + 1 for bump
+impl Space for StakePosition {
+    const INIT_SPACE: usize = 8 + 32 + 32 + 8 + (4 + 16 * MAX_FARM_REWARDS) + (4 + 8 * MAX_FARM_REWARDS) + 1;
+}
+
+impl StakePosition {
+    // Move what the stake earned since it last settled into `owed`
+    // The farm must be updated first
+    pub fn settle(&mut self, farm: &Farm) -> Result<()> {
+        // Rewards added since the position last settled start from zero
+        self.reward_per_share_paid.resize(farm.rewards.len(), 0);
+        self.owed.resize(farm.rewards.len(), 0);
+
+        for (i, reward) in farm.rewards.iter().enumerate() {
+            let earned = math::farm::earned_rewards(self.amount, reward.reward_per_share, self.reward_per_share_paid[i])?;
+            self.owed[i] = self.owed[i].checked_add(earned).ok_or(AmmError::Overflow)?;
+            self.reward_per_share_paid[i] = reward.reward_per_share;
+        }
+
+        Ok(())
+    }
+
+    // Take the whole stake out of `farm` without touching its rewards, forfeiting anything owed
+    // Returns the LP to send back, so stakers can always leave even if the reward accounting can't update
+    pub fn forfeit(&mut self, farm: &mut Farm) -> Result<u64> {
+        let amount = self.amount;
+        require!(amount > 0, AmmError::InsufficientBalance);
+
+        farm.total_staked = farm.total_staked.checked_sub(amount).ok_or(AmmError::Underflow)?;
+        self.amount = 0;
+        self.owed.iter_mut().for_each(|owed| *owed = 0);
+
+        Ok(amount)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stakers_can_leave_a_farm_whose_rewards_overflow() {
+        // A lone 1 LP stake next to a huge reward drives the reward per share towards u128::MAX
+        let mut farm = Farm {
+            config: Pubkey::new_unique(),
+            total_staked: 1,
+            last_update: 0,
+            rewards: vec![FarmReward {
+                mint: Pubkey::new_unique(),
+                emission_rate: u64::MAX,
+                reward_per_share: u128::MAX - math::WAD,
+                remaining: u64::MAX,
+            }],
+            farm_bump: 255,
+        };
+        let mut position = StakePosition {
+            owner: Pubkey::new_unique(),
+            farm: Pubkey::new_unique(),
+            amount: 1,
+            reward_per_share_paid: vec![0],
+            owed: vec![5],
+            stake_bump: 255,
+        };
+        assert!(farm.update(10).is_err());
+
+        // The stake still comes out, leaving its rewards behind
+        assert_eq!(position.forfeit(&mut farm).unwrap(), 1);
+        assert_eq!((position.amount, position.owed[0], farm.total_staked), (0, 0, 0));
+        assert!(position.forfeit(&mut farm).is_err());
+    }
+}
@@ -0,0 +1,15 @@
+use anchor_lang::prelude::*;
+
+// This struct stores the next nonce a user's signed swap intents must use
+// Every executed intent bumps it, so a signed intent can only ever execute once
+#[account]
+pub struct IntentNonce {
+    pub user: Pubkey, // User the nonce belongs to
+    pub nonce: u64, // Nonce the user's next intent must carry
+    pub nonce_bump: u8, // PDA bump for the nonce account
+}
+
+// 8 bytes for discriminator + 32 for user + 8 for nonce + 1 for bump
+impl Space for IntentNonce {
+    const INIT_SPACE: usize = 8 + 32 + 8 + 1;
+}
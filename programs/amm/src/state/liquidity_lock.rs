@@ -0,0 +1,64 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::AmmError;
+
+// This struct records LP tokens locked until a set time - it doubles as the certificate
+// anyone can read to check how much liquidity is locked, for whom, and until when
+// The LP tokens are escrowed in the lock's associated token account until they're unlocked
+#[account]
+pub struct LiquidityLock {
+    pub owner: Pubkey, // Who locked the LP and gets the rent back
+    pub beneficiary: Pubkey, // Who can unlock the LP once the lock expires
+    pub config: Pubkey, // Pool the LP belongs to
+    pub seed: u64, // Lets one owner keep several locks on the same pool
+    pub amount: u64, // LP tokens locked
+    pub unlock_time: i64, // Unix timestamp from which the LP can be unlocked
+    pub lock_bump: u8, // PDA bump for the lock account
+}
+
+// 8 bytes for discriminator + 32 for owner + 32 for beneficiary + 32 for config + 8 for seed
+// + 8 for amount + 8 for unlock time + 1 for bump
+impl Space for LiquidityLock {
+    const INIT_SPACE: usize = 8 + 32 + 32 + 32 + 8 + 8 + 8 + 1;
+}
+
+impl LiquidityLock {
+    // Split an escrow holding `escrow` LP at `now` into the locked amount for the beneficiary
+    // and anything sent to the escrow on top of it, which goes back to the owner
+    pub fn release(&self, escrow: u64, now: i64) -> Result<(u64, u64)> {
+        require!(now >= self.unlock_time, AmmError::LiquidityStillLocked);
+        let surplus = escrow.checked_sub(self.amount).ok_or(AmmError::InsufficientBalance)?;
+
+        Ok((self.amount, surplus))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lock() -> LiquidityLock {
+        LiquidityLock {
+            owner: Pubkey::new_unique(),
+            beneficiary: Pubkey::new_unique(),
+            config: Pubkey::new_unique(),
+            seed: 0,
+            amount: 1_000,
+            unlock_time: 100,
+            lock_bump: 255,
+        }
+    }
+
+    #[test]
+    fn release_waits_for_unlock_time() {
+        assert!(lock().release(1_000, 99).is_err());
+        assert_eq!(lock().release(1_000, 100).unwrap(), (1_000, 0));
+    }
+
+    #[test]
+    fn release_returns_surplus_to_owner() {
+        // LP sent to the escrow on top of the lock never reaches the beneficiary
+        assert_eq!(lock().release(1_250, 100).unwrap(), (1_000, 250));
+        assert!(lock().release(999, 100).is_err());
+    }
+}
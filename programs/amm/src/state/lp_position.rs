@@ -0,0 +1,116 @@
+use anchor_lang::prelude::*;
+
+use crate::{errors::AmmError, math, state::Config};
+
+// Largest penalty a pool can charge for withdrawing inside its holding period, in basis points
+pub const MAX_EARLY_EXIT_FEE: u16 = 1_000;
+
+// This struct tracks one depositor's liquidity in a pool, so their fee earnings and
+// impermanent loss can be reported
+// Fees are settled from the pool's fee growth accumulators whenever the position changes;
+// the deposited and withdrawn totals let the rest of the P&L be worked out against the pool price
+#[account]
+pub struct LpPosition {
+    pub owner: Pubkey, // Depositor the position belongs to
+    pub config: Pubkey, // Pool the position is in
+    pub lp: u64, // LP tokens the position holds, as seen through deposits and withdrawals
+    pub deposited_x: u64, // Total X deposited
+    pub deposited_y: u64, // Total Y deposited
+    pub withdrawn_x: u64, // Total X withdrawn
+    pub withdrawn_y: u64, // Total Y withdrawn
+    pub fee_growth_x: u128, // Pool's X fee growth when the position last settled (WAD)
+    pub fee_growth_y: u128, // Pool's Y fee growth when the position last settled (WAD)
+    pub fees_x: u64, // X fees earned up to the last settlement
+    pub fees_y: u64, // Y fees earned up to the last settlement
+    pub last_deposit: i64, // Unix timestamp of the latest deposit (or of opening), which starts the pool's holding period over
+    pub position_bump: u8, // PDA bump for the position account
+}
+
+// 8 bytes for discriminator + 32 for owner + 32 for config + 8 for LP + 8 for each deposited
+// and withdrawn total + 16 for each fee growth snapshot + 8 for each fee total + 8 for last deposit + 1 for bump
+impl Space for LpPosition {
+    const INIT_SPACE: usize = 8 + 32 + 32 + 8 + 8 + 8 + 8 + 8 + 16 + 16 + 8 + 8 + 8 + 1;
+}
+
+impl LpPosition {
+    // Add the fees the position's LP earned since it last settled, and snapshot the pool's fee growth
+    pub fn settle(&mut self, config: &Config) -> Result<()> {
+        let earned_x = fees_earned(self.lp, config.fee_growth_x, self.fee_growth_x)?;
+        let earned_y = fees_earned(self.lp, config.fee_growth_y, self.fee_growth_y)?;
+        self.fees_x = self.fees_x.checked_add(earned_x).ok_or(AmmError::Overflow)?;
+        self.fees_y = self.fees_y.checked_add(earned_y).ok_or(AmmError::Overflow)?;
+        self.fee_growth_x = config.fee_growth_x;
+        self.fee_growth_y = config.fee_growth_y;
+
+        Ok(())
+    }
+
+    // Record a deposit of `x` and `y` for `lp` LP tokens (settle first)
+    pub fn record_deposit(&mut self, x: u64, y: u64, lp: u64) -> Result<()> {
+        self.deposited_x = self.deposited_x.checked_add(x).ok_or(AmmError::Overflow)?;
+        self.deposited_y = self.deposited_y.checked_add(y).ok_or(AmmError::Overflow)?;
+        self.lp = self.lp.checked_add(lp).ok_or(AmmError::Overflow)?;
+        self.last_deposit = Clock::get()?.unix_timestamp;
+        Ok(())
+    }
+
+    // Record a withdrawal of `x` and `y` for `lp` LP tokens (settle first)
+    // LP bought elsewhere isn't tracked, so the position's LP stops at zero
+    pub fn record_withdraw(&mut self, x: u64, y: u64, lp: u64) -> Result<()> {
+        self.withdrawn_x = self.withdrawn_x.checked_add(x).ok_or(AmmError::Overflow)?;
+        self.withdrawn_y = self.withdrawn_y.checked_add(y).ok_or(AmmError::Overflow)?;
+        self.lp = self.lp.saturating_sub(lp);
+        Ok(())
+    }
+}
+
+// Fees earned by `lp` LP tokens while the fee growth went from `snapshot` to `current` (rounded down)
+fn fees_earned(lp: u64, current: u128, snapshot: u128) -> Result<u64> {
+    let growth = current.checked_sub(snapshot).ok_or(AmmError::Underflow)?;
+    math::to_u64(math::mul_div_floor_u128(lp as u128, growth, math::WAD)?)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    // A position holding `lp` LP, last deposited into at `last_deposit`
+    pub(crate) fn position(lp: u64, last_deposit: i64) -> LpPosition {
+        LpPosition {
+            owner: Pubkey::new_unique(),
+            config: Pubkey::new_unique(),
+            lp,
+            deposited_x: 0,
+            deposited_y: 0,
+            withdrawn_x: 0,
+            withdrawn_y: 0,
+            fee_growth_x: 0,
+            fee_growth_y: 0,
+            fees_x: 0,
+            fees_y: 0,
+            last_deposit,
+            position_bump: 255,
+        }
+    }
+
+    #[test]
+    fn settle_credits_fees_since_the_last_snapshot() {
+        let mut config = crate::state::config::tests::config();
+        config.record_lp_minted(4_000).unwrap();
+        let mut position = position(1_000, 0);
+
+        config.accrue_fees(true, 1_000).unwrap();
+        config.accrue_fees(false, 3).unwrap();
+        position.settle(&config).unwrap();
+        // 1,000 of 4,000 LP earns a quarter of the fees, rounded down
+        assert_eq!((position.fees_x, position.fees_y), (250, 0));
+
+        // Settling again without new fees earns nothing more
+        position.settle(&config).unwrap();
+        assert_eq!((position.fees_x, position.fees_y), (250, 0));
+
+        config.accrue_fees(true, 4_000).unwrap();
+        position.settle(&config).unwrap();
+        assert_eq!(position.fees_x, 1_250);
+    }
+}
@@ -1,3 +1,25 @@
 pub mod config;
+pub mod price_feed;
+pub mod multi_pool;
+pub mod order;
+pub mod twap;
+pub mod batch;
+pub mod swap_commit;
+pub mod intent_nonce;
+pub mod quote_fill;
+pub mod liquidity_lock;
+pub mod farm;
+pub mod lp_position;
 
-pub use config::*;
\ No newline at end of file
+pub use config::*;
+pub use price_feed::*;
+pub use multi_pool::*;
+pub use order::*;
+pub use twap::*;
+pub use batch::*;
+pub use swap_commit::*;
+pub use intent_nonce::*;
+pub use quote_fill::*;
+pub use liquidity_lock::*;
+pub use farm::*;
+pub use lp_position::*;
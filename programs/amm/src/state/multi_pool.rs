@@ -0,0 +1,146 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{associated_token::get_associated_token_address, token::TokenAccount};
+
+use crate::{errors::AmmError, math};
+
+// Maximum number of tokens a multi-asset pool can hold
+pub const MAX_ASSETS: usize = 8;
+
+// This struct stores the configuration for a multi-asset (basket) pool
+// Any two tokens can be swapped against each other, and liquidity is always
+// added and removed in proportion to every vault
+#[account]
+pub struct MultiPool {
+    pub seed: u64, // Unique identifier for this pool
+    pub authority: Option<Pubkey>, // Optional admin who can lock/unlock the pool
+    pub mints: Vec<Pubkey>, // Tokens held by the pool, in vault order
+    pub fee: u16, // Trading fee in basis points (e.g., 500 = 0.5%)
+    pub locked: bool, // Whether the pool is locked (prevents trading)
+    pub pool_bump: u8, // PDA bump for the pool account
+    pub lp_bump: u8, // PDA bump for the LP token mint
+    pub reserves: Vec<u64>, // Amount of each token the pool prices against, in vault order (tokens sent straight to a vault aren't counted)
+}
+
+// 8 bytes for discriminator + 8 for seed + 33 for authority + 4 + 32 per mint + 2 for fee + 1 for locked + 1 for each bump
+// + 4 + 8 per reserve
+impl Space for MultiPool {
+    const INIT_SPACE: usize = 8 + 8 + (1 + 32) + (4 + 32 * MAX_ASSETS) + 2 + 1 + 1 + 1 + (4 + 8 * MAX_ASSETS);
+}
+
+impl MultiPool {
+    // Load the vault for asset `index`, checking it's the pool's associated token account
+    pub fn vault<'info>(&self, pool: Pubkey, index: usize, info: &'info AccountInfo<'info>) -> Result<Account<'info, TokenAccount>> {
+        let mint = self.mints.get(index).ok_or(AmmError::InvalidToken)?;
+        require_keys_eq!(info.key(), get_associated_token_address(&pool, mint), AmmError::InvalidToken);
+
+        Account::try_from(info)
+    }
+
+    // Load a user's token account for asset `index`, checking its mint
+    pub fn token_account<'info>(&self, index: usize, info: &'info AccountInfo<'info>) -> Result<Account<'info, TokenAccount>> {
+        let mint = self.mints.get(index).ok_or(AmmError::InvalidToken)?;
+        let account: Account<'info, TokenAccount> = Account::try_from(info)?;
+        require_keys_eq!(account.mint, *mint, AmmError::InvalidToken);
+
+        Ok(account)
+    }
+
+    // Amount of each token a deposit for `amount` LP takes
+    // The first deposit (no LP yet) sets the prices with `max_amounts`, later ones match the pool's ratios (rounded up)
+    pub fn deposit_amounts(&self, amount: u64, supply: u64, max_amounts: &[u64]) -> Result<Vec<u64>> {
+        require!(max_amounts.len() == self.reserves.len(), AmmError::InvalidAmount);
+
+        let mut amounts = Vec::with_capacity(self.reserves.len());
+        for (reserve, max) in self.reserves.iter().zip(max_amounts) {
+            let deposit = match supply {
+                0 => *max,
+                _ => math::mul_div_ceil(*reserve, amount, supply)?,
+            };
+
+            // Check that the amount doesn't exceed the user's limit
+            require!(deposit <= *max, AmmError::SlippageExceeded);
+            require!(supply != 0 || deposit != 0, AmmError::InvalidAmount);
+
+            amounts.push(deposit);
+        }
+
+        Ok(amounts)
+    }
+
+    // Amount of each token burning `amount` LP returns (rounded down)
+    pub fn withdraw_amounts(&self, amount: u64, supply: u64) -> Result<Vec<u64>> {
+        require!(amount <= supply, AmmError::InsufficientBalance);
+
+        self.reserves.iter().map(|reserve| math::mul_div_floor(*reserve, amount, supply)).collect()
+    }
+
+    // Record `amount` of token `index` added to the pool's reserves
+    pub fn record_deposit(&mut self, index: usize, amount: u64) -> Result<()> {
+        let reserve = self.reserves.get_mut(index).ok_or(AmmError::InvalidToken)?;
+        *reserve = reserve.checked_add(amount).ok_or(AmmError::Overflow)?;
+        Ok(())
+    }
+
+    // Record `amount` of token `index` taken out of the pool's reserves
+    pub fn record_withdraw(&mut self, index: usize, amount: u64) -> Result<()> {
+        let reserve = self.reserves.get_mut(index).ok_or(AmmError::InvalidToken)?;
+        *reserve = reserve.checked_sub(amount).ok_or(AmmError::Underflow)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool(reserves: Vec<u64>) -> MultiPool {
+        MultiPool {
+            seed: 0,
+            authority: None,
+            mints: vec![Pubkey::new_unique(); reserves.len()],
+            fee: 30,
+            locked: false,
+            pool_bump: 255,
+            lp_bump: 255,
+            reserves,
+        }
+    }
+
+    #[test]
+    fn first_deposit_uses_max_amounts() {
+        let amounts = pool(vec![0, 0, 0]).deposit_amounts(1_000, 0, &[100, 200, 300]).unwrap();
+        assert_eq!(amounts, vec![100, 200, 300]);
+    }
+
+    #[test]
+    fn first_deposit_ignores_leftover_reserves() {
+        // Dust left behind by rounding doesn't stop the pool from restarting
+        let amounts = pool(vec![1, 0, 7]).deposit_amounts(1_000, 0, &[100, 200, 300]).unwrap();
+        assert_eq!(amounts, vec![100, 200, 300]);
+        assert!(pool(vec![0, 0, 0]).deposit_amounts(1_000, 0, &[100, 0, 300]).is_err());
+    }
+
+    #[test]
+    fn later_deposits_round_up() {
+        let pool = pool(vec![1_000, 3_000]);
+        assert_eq!(pool.deposit_amounts(1, 3, &[u64::MAX, u64::MAX]).unwrap(), vec![334, 1_000]);
+        assert!(pool.deposit_amounts(1, 3, &[333, u64::MAX]).is_err());
+    }
+
+    #[test]
+    fn withdrawals_round_down() {
+        let pool = pool(vec![1_000, 3_000]);
+        assert_eq!(pool.withdraw_amounts(1, 3).unwrap(), vec![333, 1_000]);
+        assert!(pool.withdraw_amounts(4, 3).is_err());
+    }
+
+    #[test]
+    fn records_move_reserves() {
+        let mut pool = pool(vec![1_000, 3_000]);
+        pool.record_deposit(0, 500).unwrap();
+        pool.record_withdraw(1, 1_000).unwrap();
+        assert_eq!(pool.reserves, vec![1_500, 2_000]);
+        assert!(pool.record_withdraw(1, 2_001).is_err());
+        assert!(pool.record_deposit(2, 1).is_err());
+    }
+}
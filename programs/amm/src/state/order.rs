@@ -0,0 +1,64 @@
+use anchor_lang::prelude::*;
+
+use crate::math;
+
+// Largest bounty an order can offer to the cranker that fills it, in basis points of the output
+pub const MAX_ORDER_BOUNTY: u16 = 500;
+
+// This struct stores a limit order resting against a pool
+// The input tokens are escrowed in the order's associated token account until it's filled or cancelled
+#[account]
+pub struct Order {
+    pub owner: Pubkey, // Who placed the order and receives the output
+    pub config: Pubkey, // Pool the order trades against
+    pub mint_in: Pubkey, // Token being sold
+    pub seed: u64, // Lets one owner keep several orders on the same pool
+    pub is_x: bool, // Whether the order sells token X
+    pub amount: u64, // Amount of the input token to sell
+    pub min_amount_out: u64, // Least output the owner accepts after the bounty, which sets the limit price
+    pub bounty: u16, // Part of the output paid to the cranker, in basis points
+    pub order_bump: u8, // PDA bump for the order account
+}
+
+// 8 bytes for discriminator + 32 for owner + 32 for config + 32 for mint in + 8 for seed + 1 for side
+// + 8 for amount + 8 for min amount out + 2 for bounty + 1 for bump
+impl Space for Order {
+    const INIT_SPACE: usize = 8 + 32 + 32 + 32 + 8 + 1 + 8 + 8 + 2 + 1;
+}
+
+impl Order {
+    // Split a fill's output into the owner's part and the cranker's bounty (rounded down),
+    // or None if the owner's part misses the limit price
+    pub fn split_output(&self, amount_out: u64) -> Result<Option<(u64, u64)>> {
+        let bounty = math::mul_div_floor(amount_out, self.bounty as u64, math::FEE_DENOMINATOR)?;
+        let owner_out = amount_out - bounty;
+
+        Ok((owner_out >= self.min_amount_out).then_some((owner_out, bounty)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bounty_comes_out_of_the_limit_price() {
+        let order = Order {
+            owner: Pubkey::new_unique(),
+            config: Pubkey::new_unique(),
+            mint_in: Pubkey::new_unique(),
+            seed: 0,
+            is_x: true,
+            amount: 1_000,
+            min_amount_out: 990,
+            bounty: 100,
+            order_bump: 255,
+        };
+
+        // 1% of 1,000 leaves exactly the limit, while 998 misses it even with the bounty rounded down to 9
+        assert_eq!(order.split_output(1_000).unwrap(), Some((990, 10)));
+        assert_eq!(order.split_output(999).unwrap(), Some((990, 9)));
+        assert_eq!(order.split_output(998).unwrap(), None);
+        assert_eq!(order.split_output(1_999).unwrap(), Some((1_980, 19)));
+    }
+}
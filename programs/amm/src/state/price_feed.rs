@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::AmmError;
+
+// This struct stores a reference price used by oracle-anchored (PMM) pools
+// The layout is fixed so tests and off-chain publishers can run their own feed
+#[account]
+pub struct PriceFeed {
+    pub authority: Pubkey, // Who is allowed to publish prices to this feed
+    pub price: u64, // Price of one whole X token in whole Y tokens, scaled by 10^expo
+    pub conf: u64, // Confidence interval around the price, same scale as price
+    pub expo: i32, // Decimal exponent applied to price and conf (e.g. -6)
+    pub publish_time: i64, // Unix timestamp of the last update
+}
+
+// 8 bytes for discriminator + 32 for authority + 8 for price + 8 for conf + 4 for expo + 8 for publish time
+impl Space for PriceFeed {
+    const INIT_SPACE: usize = 8 + 32 + 8 + 8 + 4 + 8;
+}
+
+impl PriceFeed {
+    // Return the price if it is fresh and tight enough to trade against
+    pub fn checked_price(&self, now: i64, max_age: u64, max_confidence: u16) -> Result<u64> {
+        require!(self.price != 0, AmmError::InvalidPrice);
+
+        let age = now.checked_sub(self.publish_time).ok_or(AmmError::Underflow)?;
+        require!(age >= 0 && age as u64 <= max_age, AmmError::StalePrice);
+
+        // conf / price must not exceed max_confidence basis points
+        require!(
+            (self.conf as u128) * 10_000 <= (self.price as u128) * (max_confidence as u128),
+            AmmError::PriceConfidenceTooLow
+        );
+
+        Ok(self.price)
+    }
+}
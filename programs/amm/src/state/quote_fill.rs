@@ -0,0 +1,47 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::AmmError;
+
+// This struct records how much of a market maker's RFQ quote has been filled
+// Its address is derived from the market maker and the quote id, so fills add up across transactions
+// and the quote can never fill for more than its size
+#[account]
+pub struct QuoteFill {
+    pub market_maker: Pubkey, // Market maker who signed the quote
+    pub id: u64, // Quote id chosen by the market maker
+    pub filled: u64, // Input filled against the quote so far
+}
+
+// 8 bytes for discriminator + 32 for market maker + 8 for id + 8 for filled
+impl Space for QuoteFill {
+    const INIT_SPACE: usize = 8 + 32 + 8 + 8;
+}
+
+impl QuoteFill {
+    // Fill as much of `amount` as is left of a quote for `amount_in`, returning the part filled
+    pub fn fill(&mut self, amount_in: u64, amount: u64) -> Result<u64> {
+        let remaining = amount_in.checked_sub(self.filled).ok_or(AmmError::Underflow)?;
+        require!(remaining != 0, AmmError::QuoteFilled);
+
+        let fill = amount.min(remaining);
+        self.filled += fill;
+
+        Ok(fill)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn partial_fills_add_up_to_the_quote() {
+        let mut quote_fill = QuoteFill { market_maker: Pubkey::new_unique(), id: 7, filled: 0 };
+        assert_eq!(quote_fill.fill(1_000, 400).unwrap(), 400);
+        assert_eq!(quote_fill.fill(1_000, 400).unwrap(), 400);
+        // Only what's left of the quote fills
+        assert_eq!(quote_fill.fill(1_000, 400).unwrap(), 200);
+        assert_eq!(quote_fill.filled, 1_000);
+        assert!(quote_fill.fill(1_000, 1).is_err());
+    }
+}
@@ -0,0 +1,83 @@
+use anchor_lang::{prelude::*, solana_program::hash::hashv};
+
+use crate::errors::AmmError;
+
+// Slots that must pass after a commit before it can be revealed
+pub const MIN_REVEAL_DELAY: u64 = 2;
+
+// Slots after a commit at which it expires; unrevealed commits can then be reclaimed
+pub const COMMIT_EXPIRY: u64 = 150;
+
+// This struct stores a committed swap waiting to be revealed
+// Only a hash of the swap is public, and the escrow can hold both tokens so the direction stays hidden
+#[account]
+pub struct SwapCommit {
+    pub owner: Pubkey, // Who committed the swap and receives the output
+    pub config: Pubkey, // Pool the swap will trade against
+    pub seed: u64, // Lets one owner keep several commits on the same pool
+    pub hash: [u8; 32], // Hash of (direction, amount, min amount out, salt)
+    pub commit_slot: u64, // Slot the swap was committed in
+    pub commit_bump: u8, // PDA bump for the commit account
+}
+
+// 8 bytes for discriminator + 32 for owner + 32 for config + 8 for seed + 32 for hash + 8 for slot + 1 for bump
+impl Space for SwapCommit {
+    const INIT_SPACE: usize = 8 + 32 + 32 + 8 + 32 + 8 + 1;
+}
+
+impl SwapCommit {
+    // Hash committed to for a swap, computed the same way off-chain
+    pub fn commitment(is_x: bool, amount: u64, min_amount_out: u64, salt: &[u8; 32]) -> [u8; 32] {
+        hashv(&[&[is_x as u8], &amount.to_le_bytes(), &min_amount_out.to_le_bytes(), salt]).to_bytes()
+    }
+
+    // Check that `slot` falls between the reveal delay and the expiry
+    pub fn check_reveal(&self, slot: u64) -> Result<()> {
+        require!(slot >= self.commit_slot.saturating_add(MIN_REVEAL_DELAY), AmmError::RevealTooEarly);
+        require!(!self.expired(slot), AmmError::CommitExpired);
+        Ok(())
+    }
+
+    // Whether the commit can no longer be revealed at `slot`, and can be reclaimed instead
+    pub fn expired(&self, slot: u64) -> bool {
+        slot >= self.commit_slot.saturating_add(COMMIT_EXPIRY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commitment_binds_every_field() {
+        let salt = [7; 32];
+        let hash = SwapCommit::commitment(true, 1_000, 900, &salt);
+
+        assert_eq!(hash, SwapCommit::commitment(true, 1_000, 900, &salt));
+        assert_ne!(hash, SwapCommit::commitment(false, 1_000, 900, &salt));
+        assert_ne!(hash, SwapCommit::commitment(true, 1_001, 900, &salt));
+        assert_ne!(hash, SwapCommit::commitment(true, 1_000, 899, &salt));
+        assert_ne!(hash, SwapCommit::commitment(true, 1_000, 900, &[8; 32]));
+    }
+
+    #[test]
+    fn reveal_window_runs_from_delay_to_expiry() {
+        let commit = SwapCommit {
+            owner: Pubkey::new_unique(),
+            config: Pubkey::new_unique(),
+            seed: 0,
+            hash: [0; 32],
+            commit_slot: 100,
+            commit_bump: 255,
+        };
+
+        assert!(commit.check_reveal(100 + MIN_REVEAL_DELAY - 1).is_err());
+        assert!(commit.check_reveal(100 + MIN_REVEAL_DELAY).is_ok());
+        assert!(commit.check_reveal(100 + COMMIT_EXPIRY - 1).is_ok());
+        assert!(!commit.expired(100 + COMMIT_EXPIRY - 1));
+
+        // Once it can't be revealed it can be reclaimed
+        assert!(commit.check_reveal(100 + COMMIT_EXPIRY).is_err());
+        assert!(commit.expired(100 + COMMIT_EXPIRY));
+    }
+}
@@ -0,0 +1,80 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::AmmError;
+
+// This struct stores a TWAP order that sells its input against a pool in equal slices
+// The input is escrowed in the order's associated token account and released one slice per interval
+#[account]
+pub struct TwapOrder {
+    pub owner: Pubkey, // Who placed the order and receives the output
+    pub config: Pubkey, // Pool the order trades against
+    pub mint_in: Pubkey, // Token being sold
+    pub seed: u64, // Lets one owner keep several TWAP orders on the same pool
+    pub is_x: bool, // Whether the order sells token X
+    pub slice_amount: u64, // Amount of the input token sold per slice
+    pub slices_left: u16, // Number of slices still to execute
+    pub interval: i64, // Seconds that must pass between slices
+    pub next_time: i64, // Unix timestamp when the next slice can execute
+    pub min_slice_out: u64, // Least output accepted for each slice, which sets the per-slice minimum price
+    pub twap_bump: u8, // PDA bump for the order account
+}
+
+// 8 bytes for discriminator + 32 for owner + 32 for config + 32 for mint in + 8 for seed + 1 for side
+// + 8 for slice amount + 2 for slices left + 8 for interval + 8 for next time + 8 for min slice out + 1 for bump
+impl Space for TwapOrder {
+    const INIT_SPACE: usize = 8 + 32 + 32 + 32 + 8 + 1 + 8 + 2 + 8 + 8 + 8 + 1;
+}
+
+impl TwapOrder {
+    // Amount the next slice sells at `now`, out of the `escrow` left
+    // The last slice sells everything left in the escrow
+    pub fn next_slice(&self, escrow: u64, now: i64) -> Result<u64> {
+        require!(now >= self.next_time, AmmError::TwapSliceNotReady);
+
+        Ok(match self.slices_left == 1 {
+            true => escrow,
+            false => self.slice_amount,
+        })
+    }
+
+    // Count a slice executed at `now` and schedule the next one, returning whether it was the last
+    pub fn advance(&mut self, now: i64) -> Result<bool> {
+        self.slices_left = self.slices_left.checked_sub(1).ok_or(AmmError::Underflow)?;
+        self.next_time = now.checked_add(self.interval).ok_or(AmmError::Overflow)?;
+
+        Ok(self.slices_left == 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slices_run_once_per_interval() {
+        let mut twap = TwapOrder {
+            owner: Pubkey::new_unique(),
+            config: Pubkey::new_unique(),
+            mint_in: Pubkey::new_unique(),
+            seed: 0,
+            is_x: true,
+            slice_amount: 100,
+            slices_left: 2,
+            interval: 60,
+            next_time: 1_000,
+            min_slice_out: 1,
+            twap_bump: 255,
+        };
+
+        assert!(twap.next_slice(200, 999).is_err());
+        assert_eq!(twap.next_slice(200, 1_000).unwrap(), 100);
+        assert!(!twap.advance(1_010).unwrap());
+
+        // The interval counts from when the slice actually ran
+        assert!(twap.next_slice(100, 1_069).is_err());
+
+        // The last slice sweeps whatever is left in the escrow
+        assert_eq!(twap.next_slice(105, 1_070).unwrap(), 105);
+        assert!(twap.advance(1_070).unwrap());
+    }
+}